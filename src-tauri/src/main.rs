@@ -1,24 +1,245 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use std::fs::{self, File};
-use std::io::{Read, Write};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::Path;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
+use aes_gcm::aead::{Aead, Payload};
+use aes_gcm::{Aes256Gcm, KeyInit};
+use chrono::{Datelike, Timelike};
 use rand::distributions::Alphanumeric;
 use rand::Rng;
 use secrecy::{ExposeSecret, Secret};
-use tauri::Emitter;
+use sevenz_rust2::encoder_options::{AesEncoderOptions, LZMA2Options};
+use tauri::{Emitter, Manager};
 
 use zip::unstable::write::FileOptionsExt;
-use zip::write::{FileOptions, ZipWriter};
+use zip::write::{FileOptions, FullFileOptions, ZipWriter};
 use zip::{AesMode, CompressionMethod};
 use walkdir::WalkDir;
 
+const MAX_TOTAL_SIZE: u64 = 10 * 1024 * 1024 * 1024; // 10 GB
+const MAX_FILE_COUNT: usize = 10_000;
+// Deflate tops out around 1030:1, only crafted data (or huge runs of zeros) gets there.
+// Entries below the minimum size aren't checked, they can't do harm whatever their ratio.
+const DEFAULT_MAX_COMPRESSION_RATIO: u64 = 1000;
+const COMPRESSION_RATIO_MIN_SIZE: u64 = 1024 * 1024;
+// Read size when copying entry data in and out of archives
+const COPY_BUFFER_SIZE: usize = 1024 * 1024;
+// Progress goes out at least this often, even when the percentage hasn't moved
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+// zstd's window at its highest levels (window log 27 at level 22, 8 MB at 19)
+const ZSTD_MAX_WINDOW_BYTES: u64 = 128 * 1024 * 1024;
+
 struct AppState {
+    jobs: JobRegistry,
+    skip_events: Mutex<SkipThrottle>,
+}
+
+// Long-running commands take an optional job id (from new_job_id) so several can run at
+// once, each with its own cancel flag and progress snapshot. Without one they get a fresh
+// id and can still be stopped by the cancel command of their kind.
+type JobId = u64;
+
+#[derive(Clone, Copy, PartialEq)]
+enum JobKind {
+    Encrypt,
+    // decrypt_file and the other commands reading an existing archive
+    Decrypt,
+    // summarize_selection and find_duplicates
+    Selection,
+}
+
+struct Job {
+    kind: JobKind,
     cancel_flag: Arc<AtomicBool>,
+    // Last values sent on the progress/status events, for polling when events get lost
+    progress: Mutex<ProgressSnapshot>,
+}
+
+#[derive(Default)]
+struct JobRegistry {
+    next_id: AtomicU64,
+    // Shared with the guards, which remove their job once it's over
+    jobs: Arc<Mutex<std::collections::HashMap<JobId, Arc<Job>>>>,
+}
+
+impl JobRegistry {
+    fn new_id(&self) -> JobId {
+        self.next_id.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    // Registers a job under the caller's id, or a fresh one; it's unregistered when the
+    // guard is dropped
+    fn start(&self, kind: JobKind, id: Option<JobId>) -> Result<JobGuard, String> {
+        let id = id.unwrap_or_else(|| self.new_id());
+        let mut jobs = self.jobs.lock().unwrap();
+        if jobs.contains_key(&id) {
+            return Err(format!("Job {} is already running", id));
+        }
+        let job = Arc::new(Job {
+            kind,
+            cancel_flag: Arc::new(AtomicBool::new(false)),
+            progress: Mutex::new(ProgressSnapshot::default()),
+        });
+        jobs.insert(id, job.clone());
+        Ok(JobGuard { id, job, jobs: self.jobs.clone() })
+    }
+
+    // False when no such job is running (already finished, or never started)
+    fn cancel(&self, id: JobId) -> bool {
+        match self.jobs.lock().unwrap().get(&id) {
+            Some(job) => {
+                job.cancel_flag.store(true, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn cancel_kind(&self, kind: JobKind) {
+        for job in self.jobs.lock().unwrap().values().filter(|job| job.kind == kind) {
+            job.cancel_flag.store(true, Ordering::SeqCst);
+        }
+    }
+
+    fn progress(&self, id: JobId) -> Option<ProgressSnapshot> {
+        self.jobs.lock().unwrap().get(&id).map(|job| job.progress.lock().unwrap().clone())
+    }
+}
+
+struct JobGuard {
+    id: JobId,
+    job: Arc<Job>,
+    jobs: Arc<Mutex<std::collections::HashMap<JobId, Arc<Job>>>>,
+}
+
+impl JobGuard {
+    fn cancel_flag(&self) -> Arc<AtomicBool> {
+        self.job.cancel_flag.clone()
+    }
+
+    // Called on the thread doing the work, so its progress events land in this job's snapshot
+    fn enter(&self) -> CurrentJob {
+        CurrentJob::enter(Some(self.job.clone()))
+    }
+}
+
+impl Drop for JobGuard {
+    fn drop(&mut self) {
+        self.jobs.lock().unwrap().remove(&self.id);
+    }
+}
+
+thread_local! {
+    // The job whose snapshot emit_progress and emit_status update on this thread
+    static CURRENT_JOB: std::cell::RefCell<Option<Arc<Job>>> = const { std::cell::RefCell::new(None) };
+}
+
+// Restores the previous current job when dropped
+struct CurrentJob {
+    previous: Option<Arc<Job>>,
+}
+
+impl CurrentJob {
+    fn enter(job: Option<Arc<Job>>) -> Self {
+        CurrentJob { previous: CURRENT_JOB.with(|current| current.replace(job)) }
+    }
+
+    // For helper threads (the 7z ticker) reporting on behalf of the current job
+    fn get() -> Option<Arc<Job>> {
+        CURRENT_JOB.with(|current| current.borrow().clone())
+    }
+}
+
+impl Drop for CurrentJob {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CURRENT_JOB.with(|current| current.replace(previous));
+    }
+}
+
+fn update_current_job(update: impl FnOnce(&mut ProgressSnapshot)) {
+    CURRENT_JOB.with(|current| {
+        if let Some(job) = current.borrow().as_ref() {
+            update(&mut job.progress.lock().unwrap());
+        }
+    });
+}
+
+#[derive(Clone, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProgressSnapshot {
+    progress: u8,
+    status: String,
+}
+
+fn emit_progress<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, progress: u8) -> tauri::Result<()> {
+    update_current_job(|snapshot| snapshot.progress = progress);
+    app_handle.emit("encryption_progress", progress)
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProgressBytes {
+    bytes_done: u64,
+    bytes_total: u64,
+}
+
+// Sent next to encryption_progress wherever progress is counted in bytes: a whole percent
+// is too coarse for a fine bar and sits at 99 for the whole last percent of a big file
+fn emit_progress_bytes<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, bytes_done: u64, bytes_total: u64) -> tauri::Result<()> {
+    app_handle.emit("encryption_progress_bytes", ProgressBytes { bytes_done, bytes_total })
+}
+
+fn emit_status<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, status: impl Into<String>) -> tauri::Result<()> {
+    let status = status.into();
+    update_current_job(|snapshot| snapshot.status = status.clone());
+    app_handle.emit("encryption_status", status)
+}
+
+// Skipping thousands of files shouldn't flood the UI: past this many file_skipped events
+// in a second the rest are only counted, the end-of-run summary still lists them all
+const SKIP_EVENTS_PER_SECOND: usize = 20;
+
+#[derive(Default)]
+struct SkipThrottle {
+    window_start: Option<Instant>,
+    sent: usize,
+    suppressed: usize,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FileSkipped {
+    path: String,
+    reason: String,
+    // Events dropped by the throttle since the previous one that went out
+    suppressed: usize,
+}
+
+// Live counterpart of the skip lists in the summaries, for a scrolling log in the UI
+fn emit_file_skipped<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, path: impl Into<String>, reason: impl Into<String>) -> tauri::Result<()> {
+    let mut suppressed = 0;
+    if let Some(state) = app_handle.try_state::<AppState>() {
+        let mut throttle = state.skip_events.lock().unwrap();
+        let now = Instant::now();
+        let in_window = throttle.window_start.is_some_and(|start| now.duration_since(start) < Duration::from_secs(1));
+        if !in_window {
+            throttle.window_start = Some(now);
+            throttle.sent = 0;
+        }
+        if throttle.sent >= SKIP_EVENTS_PER_SECOND {
+            throttle.suppressed += 1;
+            return Ok(());
+        }
+        throttle.sent += 1;
+        suppressed = std::mem::take(&mut throttle.suppressed);
+    }
+    app_handle.emit("file_skipped", FileSkipped { path: path.into(), reason: reason.into(), suppressed })
 }
 
 #[derive(serde::Deserialize, serde::Serialize)]
@@ -28,6 +249,288 @@ enum EncryptionMethod {
     SevenZip,
 }
 
+#[derive(Clone, Copy, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+enum BrokenSymlinkPolicy {
+    Skip,
+    #[default]
+    Error,
+    StoreAsLink,
+}
+
+// Named pipes, sockets and devices: opening a FIFO blocks until a writer shows up
+#[derive(Clone, Copy, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+enum SpecialFilePolicy {
+    #[default]
+    Skip,
+    Error,
+}
+
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase", default)]
+struct EncryptOptions {
+    broken_symlink_policy: BrokenSymlinkPolicy,
+    special_files_policy: SpecialFilePolicy,
+    // Opt-in, re-reads the finished archive to report per-entry sizes
+    collect_stats: bool,
+    password_policy: Option<PasswordPolicy>,
+    // Unix seconds stamped on every entry instead of the file's own mtime
+    fixed_mtime: Option<u64>,
+    // Write entries sorted by relative path instead of WalkDir's traversal order
+    deterministic: bool,
+    // None picks Zip64 per entry from its size, Some forces it on or off
+    force_zip64: Option<bool>,
+    // Entries matching these patterns (`*` and `?` wildcards) are stored without encryption
+    plaintext_patterns: Vec<String>,
+    // Zip only: compress on this many worker threads, 0 or 1 keeps the sequential path
+    parallel_workers: usize,
+    // One archive per selected path, `output_path` is then the output directory
+    separate_archives: bool,
+    // Archive name for separate_archives: {name}, {date}, {time} and {index} placeholders
+    filename_template: String,
+    // Leave out __MACOSX, AppleDouble `._*` files and .DS_Store; None means on
+    strip_mac_metadata: Option<bool>,
+    // Saved profile applied first, the options set in the same call override its values
+    #[serde(skip_serializing)]
+    profile: Option<String>,
+    // Incremental mode: only files new or changed since this manifest are archived,
+    // and the updated manifest is written next to the archive, unencrypted (so not
+    // allowed with obfuscate_names)
+    #[serde(skip_serializing)]
+    previous_manifest: Option<Manifest>,
+    // Retries for transient read errors (timeouts, busy network shares), 0 fails on the first one
+    retry_count: usize,
+    // First retry delay, doubled on each further attempt; defaults to 250 ms
+    retry_backoff_ms: Option<u64>,
+    // Write `<archive>.sha256` (or `.b3`) in the `<hash>  <filename>` format sha256sum/b3sum check
+    write_checksum_sidecar: bool,
+    checksum_algorithm: ChecksumAlgorithm,
+    // Zip only: entries are stored as 0001, 0002, ... and the real names go into an
+    // encrypted manifest.json. Only EaZip restores them, other tools extract the opaque names.
+    obfuscate_names: bool,
+    // Levels below each selected folder to include, 1 keeps only its direct children
+    max_depth: Option<usize>,
+    // Like tar --one-file-system: mount points below a selected folder are kept as empty
+    // folders, nothing on another device is walked. Unix compares device ids, Windows the
+    // volume serial numbers, so two drives mounted into NTFS folders are told apart too.
+    one_file_system: bool,
+    // Zip on Unix: store each file's uid/gid for preserve_ownership. Off by default, the
+    // field sits unencrypted next to the entry and gives the account ids away.
+    store_ownership: bool,
+    // Explicit folder entries; None means on. When off, folders only exist through the
+    // paths of the files inside them, so empty ones are lost unless keep_empty_directories
+    // is set. The 7z writer lists non-empty folders itself either way.
+    store_directory_entries: Option<bool>,
+    // With store_directory_entries off, still add entries for folders that have no content
+    keep_empty_directories: bool,
+    // Zip only: rewrite line endings of text files while they are stored, like `zip -l`/`-ll`
+    text_mode: Option<TextMode>,
+    // Zip only: stored in clear in the archive comment, anyone with the file can read it.
+    // Tied to one password, so it's never saved with a profile.
+    #[serde(skip_serializing)]
+    password_hint: Option<String>,
+    // Zip only: store already-compressed files (by extension or a sample of their first
+    // block) instead of deflating them again
+    auto_store_incompressible: bool,
+    // 7z only: key derivation cost as a power of two of SHA-256 rounds, 16 to 24, defaults
+    // to 7-Zip's 19. Zip AES can't be tuned, see zip_file_options.
+    kdf_cycles_power: Option<u8>,
+    // Method and level as a short string for scripting, e.g. "deflate:9", "zstd:19" or
+    // "7z:5", see parse_compression_spec. Unset keeps Deflate for zip, LZMA2's default for 7z.
+    compression: Option<String>,
+    // Zip only: seal the finished zip into one .eazip container so entry names, sizes
+    // and the central directory are encrypted too, see CONTAINER_MAGIC
+    whole_archive: bool,
+    // Entry paths are made relative to this directory instead of each selected path's
+    // parent; every selected path must be inside it
+    base_dir: Option<String>,
+    // Zip only: round the archive size up to a multiple of this many bytes with a
+    // padding entry, so the size says less about the content, see pad_zip
+    pad_to: Option<u64>,
+    // Zip only: also add a random amount of padding, up to 5% of the archive (the cap is at least 64 KB)
+    random_padding: bool,
+    // Push written data to disk every this many MB, so a crash or power loss halfway
+    // through a large archive leaves what was written recoverable; the finished archive
+    // is always synced before success is reported
+    sync_every_mb: Option<u64>,
+    // Files smaller or larger than these many bytes are left out; folders are still walked
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+    // Zip only: files with identical content are stored once, the other copies are listed
+    // in DEDUP_MANIFEST_ENTRY and recreated on extraction
+    deduplicate: bool,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+enum LineEnding {
+    #[default]
+    Keep,
+    Lf,
+    Crlf,
+}
+
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase", default)]
+struct TextMode {
+    eol: LineEnding,
+    // Extensions (without the dot, any case) treated as text; empty uses DEFAULT_TEXT_EXTENSIONS
+    extensions: Vec<String>,
+}
+
+const DEFAULT_TEXT_EXTENSIONS: &[&str] = &[
+    "txt", "md", "csv", "tsv", "log", "ini", "cfg", "conf", "json", "xml", "yml", "yaml", "toml",
+    "html", "htm", "css", "js", "ts", "py", "rs", "c", "h", "cpp", "java", "sh", "bat", "sql", "svg",
+];
+
+impl TextMode {
+    // The line ending to write this file with, None leaves its bytes untouched
+    fn eol_for(&self, path: &Path) -> Option<LineEnding> {
+        if self.eol == LineEnding::Keep {
+            return None;
+        }
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        let is_text = if self.extensions.is_empty() {
+            DEFAULT_TEXT_EXTENSIONS.contains(&extension.as_str())
+        } else {
+            self.extensions.iter().any(|e| e.trim_start_matches('.').eq_ignore_ascii_case(&extension))
+        };
+        is_text.then_some(self.eol)
+    }
+}
+
+// Converts CRLF and LF line breaks chunk by chunk; a lone CR is left as is
+struct EolConverter {
+    eol: LineEnding,
+    // The previous chunk ended with a CR that may start a CRLF
+    pending_cr: bool,
+}
+
+impl EolConverter {
+    fn new(eol: LineEnding) -> Self {
+        EolConverter { eol, pending_cr: false }
+    }
+
+    fn convert(&mut self, input: &[u8], out: &mut Vec<u8>) {
+        out.clear();
+        for &byte in input {
+            if self.pending_cr {
+                self.pending_cr = false;
+                if byte == b'\n' {
+                    out.extend_from_slice(if self.eol == LineEnding::Lf { b"\n" } else { b"\r\n" });
+                    continue;
+                }
+                out.push(b'\r');
+            }
+            match byte {
+                b'\r' => self.pending_cr = true,
+                b'\n' if self.eol == LineEnding::Crlf => out.extend_from_slice(b"\r\n"),
+                _ => out.push(byte),
+            }
+        }
+    }
+
+    fn finish(&mut self, out: &mut Vec<u8>) {
+        out.clear();
+        if std::mem::take(&mut self.pending_cr) {
+            out.push(b'\r');
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+enum ChecksumAlgorithm {
+    #[default]
+    Sha256,
+    Blake3,
+}
+
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase", default)]
+struct PasswordPolicy {
+    min_length: usize,
+    require_symbol: bool,
+    require_digit: bool,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EntryStats {
+    name: String,
+    original_size: u64,
+    compressed_size: u64,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EncryptionSummary {
+    message: String,
+    stats: Option<Vec<EntryStats>>,
+    // Transient I/O errors that succeeded on a later attempt
+    retries: usize,
+    // Files whose size changed while they were being archived
+    size_changed: Vec<String>,
+    // Files and folders just past max_depth that were left out; their contents aren't walked
+    skipped_by_depth: usize,
+    // Files auto_store_incompressible wrote without compression
+    auto_stored: usize,
+    skipped_by_size: SkippedBySize,
+}
+
+// Files min_size/max_size left out
+#[derive(Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SkippedBySize {
+    files: usize,
+    bytes: u64,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, serde::Deserialize)]
+enum CollisionPolicy {
+    Overwrite,
+    Skip,
+    #[default]
+    Rename,
+}
+
+#[derive(Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct DecryptOptions {
+    // Skip entries already fully extracted by a previous, interrupted run
+    resume: bool,
+    // Zip only: no total-size pass before extracting, progress follows the entry count
+    low_memory: bool,
+    // Entries differing only by case (File.txt / file.txt) on a case-insensitive target
+    case_collision_policy: CollisionPolicy,
+    // Also extract archives found among the extracted files, each into its own folder.
+    // All levels together stay within the usual size and file count limits.
+    extract_recursive: bool,
+    // Levels of nesting to follow, defaults to 3 and is capped at 10
+    max_nested_depth: Option<usize>,
+    // Tried after the main password on nested encrypted archives
+    nested_passwords: Vec<Secret<String>>,
+    // Refuse the whole archive if any entry name is absolute or climbs out with `..`,
+    // instead of extracting the zip entries under their mangled names
+    reject_absolute_paths: bool,
+    // Entries whose target can't be written (read-only file or folder) are skipped and
+    // listed in the summary instead of aborting the extraction
+    skip_unwritable: bool,
+    // Zip on Unix: give entries the uid/gid stored with them (store_ownership), which needs root
+    preserve_ownership: bool,
+    // 7z only: entries that fail to decode are left out and reported instead of aborting,
+    // to salvage what's readable from a damaged archive
+    skip_errors: bool,
+    // Zip only: codepage for entry names without the UTF-8 flag (e.g. "windows-1251",
+    // "shift_jis"), CP437 as the spec says when unset
+    encoding: Option<String>,
+    // Relocates entries inside output_dir as (pattern, replacement) pairs, see map_output_path
+    path_map: Vec<(String, String)>,
+    // Unix seconds: files stored with an older modification time are left out. Folders
+    // are still created, and files without a stored time are always extracted.
+    modified_after: Option<u64>,
+    // Zip only: refuse the archive when an entry declares more than this many bytes per
+    // compressed byte, defaults to DEFAULT_MAX_COMPRESSION_RATIO, 0 turns the check off
+    max_compression_ratio: Option<u64>,
+}
+
 #[derive(serde::Serialize)]
 #[serde(rename_all = "camelCase")]
 struct FileMetadata {
@@ -40,13 +543,381 @@ struct FileMetadata {
     debug_info: Option<String>,
 }
 
+#[derive(Default, serde::Serialize)]
+struct SourceCheck {
+    missing: Vec<String>,
+    inaccessible: Vec<String>,
+}
+
+#[derive(Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SelectionSummary {
+    file_count: usize,
+    dir_count: usize,
+    total_size: u64,
+    has_symlinks: bool,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArchiveEntry {
+    name: String,
+    is_dir: bool,
+    size: u64,
+    compressed_size: u64,
+    crc32: u32,
+    encrypted: bool,
+    error: Option<String>,
+    // Unix seconds, when the archive stores a modification time
+    modified: Option<u64>,
+}
+
+fn unix_secs(time: std::time::SystemTime) -> Option<u64> {
+    time.duration_since(std::time::UNIX_EPOCH).ok().map(|d| d.as_secs())
+}
+
+impl ArchiveEntry {
+    // Placeholder row for an entry whose metadata couldn't be read
+    fn damaged(name: String, error: String) -> Self {
+        ArchiveEntry {
+            name,
+            is_dir: false,
+            size: 0,
+            compressed_size: 0,
+            crc32: 0,
+            encrypted: false,
+            error: Some(error),
+            modified: None,
+        }
+    }
+}
+
+fn is_7z_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.eq_ignore_ascii_case("7z"))
+        .unwrap_or(false)
+}
+
+struct ScannedEntry {
+    name: String,
+    header_offset: u64,
+    data_start: u64,
+    flags: u16,
+    method: u16,
+    // Key strength byte of the WinZip AES extra field (1 = 128, 2 = 192, 3 = 256 bits)
+    aes_strength: Option<u8>,
+    crc32: u32,
+    compressed_size: u64,
+    size: u64,
+    error: Option<String>,
+}
+
+fn find_signature<R: Read + Seek>(reader: &mut R, from: u64, signature: &[u8; 4]) -> std::io::Result<Option<u64>> {
+    reader.seek(SeekFrom::Start(from))?;
+    let mut buffer = vec![0; 64 * 1024];
+    let mut window: Vec<u8> = Vec::new();
+    let mut window_start = from;
+    loop {
+        let bytes_read = reader.read(&mut buffer)?;
+        if bytes_read == 0 {
+            return Ok(None);
+        }
+        window.extend_from_slice(&buffer[..bytes_read]);
+        if let Some(pos) = window.windows(4).position(|w| w == signature) {
+            return Ok(Some(window_start + pos as u64));
+        }
+        // Keep the last 3 bytes in case the signature straddles two reads
+        let keep = window.len().min(3);
+        window_start += (window.len() - keep) as u64;
+        window.drain(..window.len() - keep);
+    }
+}
+
+// Walks the local file headers from the start of a zip without relying on the
+// central directory, stopping at the first entry that can't be read completely
+fn scan_local_headers<R: Read + Seek>(reader: &mut R) -> std::io::Result<Vec<ScannedEntry>> {
+    let file_len = reader.seek(SeekFrom::End(0))?;
+    let mut entries = Vec::new();
+    let mut offset = 0u64;
+
+    while offset + 30 <= file_len {
+        reader.seek(SeekFrom::Start(offset))?;
+        let mut header = [0u8; 30];
+        reader.read_exact(&mut header)?;
+        if &header[0..4] != b"PK\x03\x04" {
+            break;
+        }
+
+        let u16_at = |i: usize| u16::from_le_bytes([header[i], header[i + 1]]);
+        let u32_at = |i: usize| u32::from_le_bytes([header[i], header[i + 1], header[i + 2], header[i + 3]]);
+        let flags = u16_at(6);
+        let method = u16_at(8);
+        let mut crc32 = u32_at(14);
+        let mut compressed_size = u32_at(18) as u64;
+        let mut size = u32_at(22) as u64;
+        let name_len = u16_at(26) as u64;
+        let extra_len = u16_at(28) as u64;
+        let data_start = offset + 30 + name_len + extra_len;
+
+        if data_start > file_len {
+            entries.push(ScannedEntry {
+                name: format!("#{}", entries.len()),
+                header_offset: offset,
+                data_start,
+                flags,
+                method,
+                aes_strength: None,
+                crc32,
+                compressed_size: 0,
+                size: 0,
+                error: Some("Entry header truncated".to_string()),
+            });
+            break;
+        }
+
+        let mut name = vec![0; name_len as usize];
+        reader.read_exact(&mut name)?;
+        let mut extra = vec![0; extra_len as usize];
+        reader.read_exact(&mut extra)?;
+        let name = String::from_utf8_lossy(&name).into_owned();
+
+        let mut aes_strength = None;
+        let mut i = 0;
+        while i + 4 <= extra.len() {
+            let id = u16::from_le_bytes([extra[i], extra[i + 1]]);
+            let len = u16::from_le_bytes([extra[i + 2], extra[i + 3]]) as usize;
+            let field = &extra[i + 4..(i + 4 + len).min(extra.len())];
+            // Zip64 extra field carries the real sizes when the 32-bit ones are saturated
+            if id == 0x0001 && field.len() >= 16 && (size == 0xFFFF_FFFF || compressed_size == 0xFFFF_FFFF) {
+                size = u64::from_le_bytes(field[0..8].try_into().unwrap());
+                compressed_size = u64::from_le_bytes(field[8..16].try_into().unwrap());
+            }
+            if id == 0x9901 && field.len() >= 5 {
+                aes_strength = Some(field[4]);
+            }
+            i += 4 + len;
+        }
+
+        let mut next_offset = data_start + compressed_size;
+        let mut error = None;
+
+        // Streamed entries store their sizes in a data descriptor after the data
+        if flags & 0x0008 != 0 && compressed_size == 0 {
+            match find_signature(reader, data_start, b"PK\x07\x08")? {
+                Some(descriptor) if descriptor + 16 <= file_len => {
+                    reader.seek(SeekFrom::Start(descriptor + 4))?;
+                    let mut d = [0u8; 12];
+                    reader.read_exact(&mut d)?;
+                    crc32 = u32::from_le_bytes([d[0], d[1], d[2], d[3]]);
+                    compressed_size = u32::from_le_bytes([d[4], d[5], d[6], d[7]]) as u64;
+                    size = u32::from_le_bytes([d[8], d[9], d[10], d[11]]) as u64;
+                    next_offset = descriptor + 16;
+                }
+                _ => error = Some("Entry data truncated".to_string()),
+            }
+        } else if next_offset > file_len {
+            error = Some("Entry data truncated".to_string());
+        }
+
+        let stop = error.is_some();
+        entries.push(ScannedEntry {
+            name,
+            header_offset: offset,
+            data_start,
+            flags,
+            method,
+            aes_strength,
+            crc32,
+            compressed_size,
+            size,
+            error,
+        });
+        if stop {
+            break;
+        }
+        offset = next_offset;
+    }
+
+    Ok(entries)
+}
+
+// Names the encryption scheme of an entry when the zip crate cannot handle it
+fn unsupported_encryption(entry: &ScannedEntry) -> Option<String> {
+    if entry.flags & 0x0001 == 0 {
+        return None;
+    }
+    let scheme = match (entry.method, entry.aes_strength) {
+        (99, Some(1)) => "AES-128".to_string(),
+        (99, Some(2)) => "AES-192".to_string(),
+        (99, Some(3)) => "AES-256".to_string(),
+        (99, Some(strength)) => return Some(format!("Unsupported encryption (AES with unknown key strength {})", strength)),
+        (99, None) => return Some("Malformed AES entry (missing AES extra field)".to_string()),
+        _ => "ZipCrypto".to_string(),
+    };
+
+    if entry.flags & 0x2000 != 0 {
+        Some(format!("Unsupported encryption ({} header-encrypted)", scheme))
+    } else if entry.flags & 0x0040 != 0 {
+        Some("Unsupported encryption (PKWARE strong encryption)".to_string())
+    } else {
+        None
+    }
+}
+
+// The zip crate reports unsupported schemes as generic parse/CRC failures,
+// look at the local headers to give the user something actionable instead
+fn zip_error_message(path: &Path, error: zip::result::ZipError) -> String {
+    if let zip::result::ZipError::InvalidPassword = error {
+        return "Mot de passe incorrect".to_string();
+    }
+    let explained = File::open(path)
+        .and_then(|mut file| scan_local_headers(&mut file))
+        .ok()
+        .and_then(|entries| entries.iter().find_map(unsupported_encryption));
+    explained.unwrap_or_else(|| error.to_string())
+}
+
+fn validate_password(password: &str, policy: &PasswordPolicy) -> Result<(), String> {
+    if password.is_empty() {
+        return Err("Password must not be empty".to_string());
+    }
+    let length = password.chars().count();
+    if length < policy.min_length {
+        return Err(format!(
+            "Password is too short ({} characters, minimum {})",
+            length, policy.min_length
+        ));
+    }
+    if policy.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+        return Err("Password must contain at least one digit".to_string());
+    }
+    if policy.require_symbol && !password.chars().any(|c| !c.is_alphanumeric() && !c.is_whitespace()) {
+        return Err("Password must contain at least one symbol".to_string());
+    }
+    Ok(())
+}
+
+fn is_rar_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.eq_ignore_ascii_case("rar"))
+        .unwrap_or(false)
+}
+
+fn rar_error(e: unrar::error::UnrarError) -> String {
+    match e.code {
+        unrar::error::Code::BadPassword => "Mot de passe incorrect".to_string(),
+        unrar::error::Code::MissingPassword => "Password required".to_string(),
+        _ => e.to_string(),
+    }
+}
+
+#[derive(Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct PasswordOptions {
+    // Defaults to DEFAULT_PASSWORD_LENGTH
+    length: Option<usize>,
+    include_symbols: bool,
+}
+
+const DEFAULT_PASSWORD_LENGTH: usize = 16;
+const MAX_PASSWORD_LENGTH: usize = 256;
+const MAX_PASSWORD_BATCH: usize = 1000;
+const PASSWORD_SYMBOLS: &[u8] = b"!#$%&*+-=?@^_~";
+
+fn random_password<R: Rng>(rng: &mut R, options: &PasswordOptions) -> Result<String, String> {
+    let length = options.length.unwrap_or(DEFAULT_PASSWORD_LENGTH);
+    if length == 0 || length > MAX_PASSWORD_LENGTH {
+        return Err(format!("Password length must be between 1 and {}", MAX_PASSWORD_LENGTH));
+    }
+
+    if !options.include_symbols {
+        return Ok(rng.sample_iter(&Alphanumeric).take(length).map(char::from).collect());
+    }
+    let charset: Vec<u8> = (b'A'..=b'Z')
+        .chain(b'a'..=b'z')
+        .chain(b'0'..=b'9')
+        .chain(PASSWORD_SYMBOLS.iter().copied())
+        .collect();
+    Ok((0..length)
+        .map(|_| charset[rng.gen_range(0..charset.len())] as char)
+        .collect())
+}
+
 #[tauri::command]
-fn generate_password() -> String {
-    rand::thread_rng()
-        .sample_iter(&Alphanumeric)
-        .take(16)
-        .map(char::from)
-        .collect()
+fn generate_password(options: Option<PasswordOptions>) -> Result<String, String> {
+    random_password(&mut rand::thread_rng(), &options.unwrap_or_default())
+}
+
+// Every password is drawn separately from the thread RNG, none is derived from another
+#[tauri::command]
+fn generate_passwords(count: usize, options: Option<PasswordOptions>) -> Result<Vec<String>, String> {
+    if count == 0 || count > MAX_PASSWORD_BATCH {
+        return Err(format!("Password count must be between 1 and {}", MAX_PASSWORD_BATCH));
+    }
+    let options = options.unwrap_or_default();
+    let mut rng = rand::thread_rng();
+    (0..count).map(|_| random_password(&mut rng, &options)).collect()
+}
+
+// Metadata of a selected path, resolved through a symlink; the flag tells a broken link
+// apart from a path that isn't there at all
+fn resolve_source(path: &Path) -> (bool, std::io::Result<fs::Metadata>) {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_symlink() => (true, fs::metadata(path)),
+        Ok(meta) => (false, Ok(meta)),
+        Err(e) => (false, Err(e)),
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DefaultsInfo {
+    buffer_size: usize,
+    progress_interval_ms: u64,
+    max_total_size: u64,
+    max_file_count: usize,
+    default_password_length: usize,
+    max_password_length: usize,
+    max_password_batch: usize,
+    default_7z_kdf_cycles_power: u8,
+    min_7z_kdf_cycles_power: u8,
+    max_7z_kdf_cycles_power: u8,
+    max_nested_depth: usize,
+    max_compression_ratio: u64,
+    text_preview_max_bytes: usize,
+    range_read_max_bytes: u64,
+    thumbnail_max_dim: u32,
+    // Zip zstd: the window size comes with the level, the zip writer builds its encoder
+    // from the level alone and takes no window size or long-distance matching
+    zstd_window_tuning: bool,
+    // Window at level 22, what extracting such an entry may need in memory too
+    zstd_max_window_bytes: u64,
+}
+
+// Read from the same constants the commands use, so the UI never keeps its own copy
+#[tauri::command]
+fn get_defaults() -> DefaultsInfo {
+    DefaultsInfo {
+        buffer_size: COPY_BUFFER_SIZE,
+        progress_interval_ms: PROGRESS_INTERVAL.as_millis() as u64,
+        max_total_size: MAX_TOTAL_SIZE,
+        max_file_count: MAX_FILE_COUNT,
+        default_password_length: DEFAULT_PASSWORD_LENGTH,
+        max_password_length: MAX_PASSWORD_LENGTH,
+        max_password_batch: MAX_PASSWORD_BATCH,
+        default_7z_kdf_cycles_power: DEFAULT_7Z_KDF_CYCLES_POWER,
+        min_7z_kdf_cycles_power: MIN_7Z_KDF_CYCLES_POWER,
+        max_7z_kdf_cycles_power: MAX_7Z_KDF_CYCLES_POWER,
+        max_nested_depth: MAX_NESTED_DEPTH,
+        max_compression_ratio: DEFAULT_MAX_COMPRESSION_RATIO,
+        text_preview_max_bytes: TEXT_PREVIEW_MAX_BYTES,
+        range_read_max_bytes: RANGE_READ_MAX_BYTES,
+        thumbnail_max_dim: THUMBNAIL_MAX_DIM,
+        zstd_window_tuning: false,
+        zstd_max_window_bytes: ZSTD_MAX_WINDOW_BYTES,
+    }
 }
 
 #[tauri::command]
@@ -60,29 +931,10 @@ fn get_file_metadata(paths: Vec<String>) -> Vec<FileMetadata> {
                 .unwrap_or(&path_str)
                 .to_string();
 
-            // Use symlink_metadata to detect symlinks
-            let (is_dir, is_symlink, size, error) = match fs::symlink_metadata(&path) {
-                Ok(meta) => {
-                    let is_symlink = meta.file_type().is_symlink();
-                    let mut is_dir = meta.is_dir();
-                    let mut size = meta.len();
-                    let mut error = None;
-
-                    // If it's a symlink, resolve it to check if it points to a dir
-                    if is_symlink {
-                        match fs::metadata(&path) {
-                            Ok(resolved_meta) => {
-                                is_dir = resolved_meta.is_dir();
-                                size = resolved_meta.len();
-                            }
-                            Err(e) => {
-                                error = Some(format!("Symlink broken: {}", e));
-                            }
-                        }
-                    }
-                    (is_dir, is_symlink, size, error)
-                }
-                Err(e) => (false, false, 0, Some(e.to_string())),
+            let (is_dir, is_symlink, size, error) = match resolve_source(path) {
+                (is_symlink, Ok(meta)) => (meta.is_dir(), is_symlink, meta.len(), None),
+                (true, Err(e)) => (false, true, 0, Some(format!("Symlink broken: {}", e))),
+                (false, Err(e)) => (false, false, 0, Some(e.to_string())),
             };
             
             // Debug: If it's supposed to be a dir but isn't, get raw mode
@@ -132,450 +984,6535 @@ fn get_file_metadata(paths: Vec<String>) -> Vec<FileMetadata> {
         .collect()
 }
 
-struct CollectedEntry {
-    abs_path: std::path::PathBuf,
-    rel_path: std::path::PathBuf,
-    is_dir: bool,
+// Checked before encrypt_files so a stale selection is reported up front rather than
+// aborting halfway. Only the selected paths are looked at, not what's below a folder:
+// a broken link counts as missing, a folder that can't be listed or a file that can't
+// be opened as inaccessible.
+#[tauri::command]
+async fn validate_sources(paths: Vec<String>) -> Result<SourceCheck, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut check = SourceCheck::default();
+        for path_str in paths {
+            let path = Path::new(&path_str);
+            let readable = match resolve_source(path) {
+                (_, Ok(meta)) if meta.is_dir() => fs::read_dir(path).map(|_| ()),
+                (_, Ok(_)) => File::open(path).map(|_| ()),
+                (_, Err(e)) => Err(e),
+            };
+            match readable {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => check.missing.push(path_str),
+                Err(_) => check.inaccessible.push(path_str),
+            }
+        }
+        check
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+// Aggregates only, for the selection footer. Links below the selected paths are counted
+// as files and not followed, the same way collect_entries sees them; unreadable entries
+// are left out rather than failing the whole rollup.
+#[tauri::command]
+async fn summarize_selection(state: tauri::State<'_, AppState>, paths: Vec<String>, job_id: Option<JobId>) -> Result<SelectionSummary, String> {
+    let job = state.jobs.start(JobKind::Selection, job_id)?;
+    let cancel_flag = job.cancel_flag();
+    tauri::async_runtime::spawn_blocking(move || {
+        let _job = job.enter();
+        let mut summary = SelectionSummary::default();
+        for path in &paths {
+            for entry in WalkDir::new(path) {
+                if cancel_flag.load(Ordering::SeqCst) {
+                    return Err("Selection summary cancelled by user.".to_string());
+                }
+                let Ok(entry) = entry else { continue };
+                if entry.path_is_symlink() {
+                    summary.has_symlinks = true;
+                }
+                if entry.file_type().is_dir() {
+                    summary.dir_count += 1;
+                } else {
+                    summary.file_count += 1;
+                    summary.total_size = summary.total_size.saturating_add(entry.metadata().map_or(0, |m| m.len()));
+                }
+            }
+        }
+        Ok(summary)
+    }).await.map_err(|e| e.to_string())?
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DuplicateGroup {
+    // BLAKE3 of the shared content
+    hash: String,
     size: u64,
+    paths: Vec<String>,
 }
 
-fn collect_entries(
-    file_paths: &[String],
-    canonical_output_path: &Path,
-) -> Result<(Vec<CollectedEntry>, u64), String> {
-    let mut entries = Vec::new();
-    let mut total_size = 0u64;
+// Files only get fully hashed when their size and first 64 KB match another file's
+const DUPLICATE_PREFIX_LEN: u64 = 64 * 1024;
 
-    for file_path_str in file_paths {
-        let root = Path::new(file_path_str);
-        let parent = root.parent().unwrap_or(Path::new("/"));
+fn prefix_hash(path: &Path) -> std::io::Result<blake3::Hash> {
+    let mut hasher = blake3::Hasher::new();
+    std::io::copy(&mut File::open(path)?.take(DUPLICATE_PREFIX_LEN), &mut hasher)?;
+    Ok(hasher.finalize())
+}
 
-        for entry in WalkDir::new(root) {
-            let entry = entry.map_err(|e| e.to_string())?;
-            let entry_path = entry.path();
+// Groups of indices into `files` (path and size) whose contents are identical, each in
+// ascending order, with the BLAKE3 they share. Unreadable files are left out of every group.
+fn duplicate_groups<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    cancel_flag: &AtomicBool,
+    files: &[(std::path::PathBuf, u64)],
+    cancelled_message: &str,
+) -> Result<Vec<(String, Vec<usize>)>, String> {
+    let mut by_size = std::collections::HashMap::<u64, Vec<usize>>::new();
+    for (index, (_, size)) in files.iter().enumerate() {
+        if *size > 0 {
+            by_size.entry(*size).or_default().push(index);
+        }
+    }
 
-            // Optimization: Only check full path if file name matches output file name
-            // This prevents the infinite recursion loop without checking every single file
-            if let Some(name) = canonical_output_path.file_name() {
-                if entry.file_name() == name {
-                    if let Ok(p) = entry_path.canonicalize() {
-                        if p == canonical_output_path {
-                            continue;
-                        }
-                    }
-                }
+    let mut by_prefix = std::collections::HashMap::<(u64, blake3::Hash), Vec<usize>>::new();
+    for (size, indices) in by_size.into_iter().filter(|(_, indices)| indices.len() > 1) {
+        for index in indices {
+            if cancel_flag.load(Ordering::SeqCst) {
+                return Err(cancelled_message.to_string());
             }
+            match prefix_hash(&files[index].0) {
+                Ok(hash) => by_prefix.entry((size, hash)).or_default().push(index),
+                Err(e) => log::warn!("Skipping {}: {}", files[index].0.display(), e),
+            }
+        }
+    }
+    by_prefix.retain(|_, indices| indices.len() > 1);
 
-            let rel = entry_path
-                .strip_prefix(parent)
-                .map_err(|e| e.to_string())?
-                .to_path_buf();
-
-            let is_dir = entry.file_type().is_dir();
-            let size = if is_dir {
-                0
+    // Files no longer than the prefix are already fully hashed
+    let total = by_prefix
+        .iter()
+        .filter(|((size, _), _)| *size > DUPLICATE_PREFIX_LEN)
+        .map(|((size, _), indices)| size * indices.len() as u64)
+        .sum();
+    let mut progress = ProgressTracker::new(app_handle, total);
+    let mut groups = Vec::new();
+    for ((size, prefix), indices) in by_prefix {
+        let mut by_hash = std::collections::HashMap::<String, Vec<usize>>::new();
+        for index in indices {
+            let hash = if size <= DUPLICATE_PREFIX_LEN {
+                prefix.to_hex().to_string()
             } else {
-                entry.metadata().map_err(|e| e.to_string())?.len()
+                match hash_file(&files[index].0, ChecksumAlgorithm::Blake3, cancel_flag, &mut progress, cancelled_message) {
+                    Ok(hash) => hash,
+                    Err(_) if cancel_flag.load(Ordering::SeqCst) => return Err(cancelled_message.to_string()),
+                    Err(e) => {
+                        log::warn!("Skipping {}: {}", files[index].0.display(), e);
+                        continue;
+                    }
+                }
             };
-
-            if !is_dir {
-                total_size = total_size.saturating_add(size);
-            }
-
-            entries.push(CollectedEntry {
-                abs_path: entry_path.to_path_buf(),
-                rel_path: rel,
-                is_dir,
-                size,
-            });
+            by_hash.entry(hash).or_default().push(index);
         }
+        groups.extend(by_hash.into_iter().filter(|(_, indices)| indices.len() > 1).map(|(hash, mut indices)| {
+            indices.sort_unstable();
+            (hash, indices)
+        }));
     }
-
-    Ok((entries, total_size))
+    Ok(groups)
 }
 
+// Groups of files with identical content, largest first. Empty files and symlinks are
+// left out, and unreadable files are skipped rather than failing the whole search.
 #[tauri::command]
-async fn encrypt_files(
+async fn find_duplicates(
     app_handle: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
-    file_paths: Vec<String>,
-    output_path: String,
-    password: Secret<String>,
-    encryption_method: EncryptionMethod,
-) -> Result<String, String> {
-    let cancel_flag = state.cancel_flag.clone(); // Clone Arc for thread
-    let password = password.expose_secret().clone(); // Clone password string
-
+    paths: Vec<String>,
+    job_id: Option<JobId>,
+) -> Result<Vec<DuplicateGroup>, String> {
+    let job = state.jobs.start(JobKind::Selection, job_id)?;
+    let cancel_flag = job.cancel_flag();
     tauri::async_runtime::spawn_blocking(move || {
-        cancel_flag.store(false, Ordering::SeqCst);
-        
-        app_handle.emit("encryption_status", "Analyse des fichiers...").unwrap();
+        let _job = job.enter();
+        let cancelled = "Duplicate search cancelled by user.";
+        emit_status(&app_handle, "Recherche des doublons...").map_err(|e| e.to_string())?;
 
-        // Canonicalize output path to prevent recursion
-        let canonical_output_path = Path::new(&output_path).canonicalize().unwrap_or_else(|_| Path::new(&output_path).to_path_buf());
+        let mut files = Vec::new();
+        for path in &paths {
+            for entry in WalkDir::new(path) {
+                if cancel_flag.load(Ordering::SeqCst) {
+                    return Err(cancelled.to_string());
+                }
+                let Ok(entry) = entry else { continue };
+                if entry.file_type().is_file() {
+                    let size = entry.metadata().map_or(0, |m| m.len());
+                    files.push((entry.into_path(), size));
+                }
+            }
+        }
 
-        // Single pass collection
-        let (entries, total_size) = collect_entries(&file_paths, &canonical_output_path)?;
+        let mut groups: Vec<DuplicateGroup> = duplicate_groups(&app_handle, &cancel_flag, &files, cancelled)?
+            .into_iter()
+            .map(|(hash, indices)| {
+                let mut paths: Vec<String> = indices.iter().map(|&i| files[i].0.to_string_lossy().into_owned()).collect();
+                paths.sort();
+                DuplicateGroup { hash, size: files[indices[0]].1, paths }
+            })
+            .collect();
+        groups.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.paths.cmp(&b.paths)));
+        emit_progress(&app_handle, 100).map_err(|e| e.to_string())?;
+        Ok(groups)
+    }).await.map_err(|e| e.to_string())?
+}
 
-        match encryption_method {
-            EncryptionMethod::SevenZip => {
-                let temp_dir = tempfile::tempdir().map_err(|e| e.to_string())?;
-                let temp_dir_path = temp_dir.path().to_path_buf();
+// WinZip AES encrypts each entry with AES-CTR and authenticates it with a truncated
+// HMAC-SHA1, checked when the entry has been read in full. The zip crate writes AE-1 (the
+// real CRC is kept) for entries of 20 bytes or more and AE-2 (CRC stored as 0) below that. Keys come from PBKDF2-HMAC-SHA1,
+// a random salt and a fixed 1000 iterations; the zip crate follows the spec, and other
+// readers wouldn't accept more. AES-GCM over the whole zip is the whole_archive option.
+fn zip_file_options<'k>(encryption_method: &EncryptionMethod, password: &'k str) -> FullFileOptions<'k> {
+    match encryption_method {
+        EncryptionMethod::Aes256 => FileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .with_aes_encryption(AesMode::Aes256, password),
+        EncryptionMethod::CryptoZip => FileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .with_deprecated_encryption(password.as_bytes()),
+        EncryptionMethod::SevenZip => unreachable!(),
+    }
+}
 
-                app_handle.emit("encryption_status", "Préparation de la copie...").unwrap();
-                app_handle.emit("encryption_progress", 0).unwrap(); // Stage 1: Setup
+const INCOMPRESSIBLE_EXTENSIONS: &[&str] = &[
+    "zip", "7z", "rar", "gz", "tgz", "bz2", "xz", "zst", "lz4", "jpg", "jpeg", "png", "gif", "webp",
+    "heic", "avif", "mp3", "m4a", "aac", "ogg", "opus", "flac", "mp4", "m4v", "mkv", "webm", "avi",
+    "mov", "docx", "xlsx", "pptx", "odt", "ods", "epub", "apk", "jar",
+];
+const ENTROPY_SAMPLE_SIZE: usize = 64 * 1024;
+// Bits per byte above which deflate gains next to nothing
+const INCOMPRESSIBLE_ENTROPY: f64 = 7.5;
 
-                let mut bytes_copied: u64 = 0;
-                let mut last_update_time = Instant::now();
-                let mut last_progress_percent: u8 = 0;
+fn is_incompressible(entry: &CollectedEntry) -> bool {
+    if entry.is_dir || entry.link_target.is_some() || entry.size == 0 {
+        return false;
+    }
+    let extension = entry.rel_path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase);
+    if extension.is_some_and(|e| INCOMPRESSIBLE_EXTENSIONS.contains(&e.as_str())) {
+        return true;
+    }
 
-                for entry in &entries {
-                    if cancel_flag.load(Ordering::SeqCst) {
-                        return Err("Encryption cancelled by user.".to_string());
-                    }
+    let mut sample = Vec::with_capacity(ENTROPY_SAMPLE_SIZE);
+    let read = File::open(&entry.abs_path).and_then(|f| f.take(ENTROPY_SAMPLE_SIZE as u64).read_to_end(&mut sample));
+    if read.is_err() || sample.is_empty() {
+        // Left to the writer, which reports the read error properly
+        return false;
+    }
+    let mut counts = [0usize; 256];
+    for &byte in &sample {
+        counts[byte as usize] += 1;
+    }
+    let len = sample.len() as f64;
+    let entropy: f64 = counts
+        .iter()
+        .filter(|&&c| c > 0)
+        .map(|&c| {
+            let p = c as f64 / len;
+            -p * p.log2()
+        })
+        .sum();
+    entropy > INCOMPRESSIBLE_ENTROPY
+}
 
-                    let dest_path = temp_dir_path.join(&entry.rel_path);
+// sevenz_rust2 defaults to 2^8 rounds, far below what 7-Zip itself writes
+const DEFAULT_7Z_KDF_CYCLES_POWER: u8 = 19;
+const MIN_7Z_KDF_CYCLES_POWER: u8 = 16;
+// 7-Zip refuses to open archives asking for more
+const MAX_7Z_KDF_CYCLES_POWER: u8 = 24;
 
-                    if entry.is_dir {
-                        fs::create_dir_all(&dest_path).map_err(|e| e.to_string())?;
-                    } else {
-                        if let Some(p) = dest_path.parent() {
-                            fs::create_dir_all(p).map_err(|e| e.to_string())?;
-                        }
-                        fs::copy(&entry.abs_path, &dest_path).map_err(|e| e.to_string())?;
-                        
-                        bytes_copied += entry.size;
-                        // Progress from 0% to 50% during copy
-                        let progress = if total_size > 0 {
-                            (bytes_copied as f64 / total_size as f64 * 50.0) as u8
-                        } else {
-                            0
-                        };
-                        
-                        let now = Instant::now();
-                        if progress > last_progress_percent || now.duration_since(last_update_time) >= Duration::from_millis(100) {
-                             app_handle.emit("encryption_progress", progress).unwrap();
-                             app_handle.emit("encryption_status", format!("Copie: {}", entry.abs_path.file_name().and_then(|n| n.to_str()).unwrap_or("..."))).unwrap();
-                             last_update_time = now;
-                             last_progress_percent = progress;
-                        }
-                    }
-                }
+fn seven_zip_aes_options(password: &str, kdf_cycles_power: u8) -> AesEncoderOptions {
+    let mut aes = AesEncoderOptions::new(password.into());
+    aes.num_cycles_power = kdf_cycles_power;
+    aes
+}
 
-                app_handle.emit("encryption_progress", 50).unwrap(); // Stage 2: Copying complete
-                app_handle.emit("encryption_progress", 50).unwrap(); // Stage 2: Copying complete
-                app_handle.emit("encryption_status", "Compression de l'archive (cette étape peut être longue)...").unwrap();
+// Windows caps ordinary paths at 260 characters, the \\?\ form lifts that for the 7z
+// staging copy, which nests the whole selection under the temp folder
+#[cfg(windows)]
+fn extended_length_path(path: &Path) -> std::path::PathBuf {
+    let raw = path.as_os_str().to_string_lossy();
+    if raw.starts_with(r"\\?\") || !path.is_absolute() {
+        path.to_path_buf()
+    } else if let Some(share) = raw.strip_prefix(r"\\") {
+        std::path::PathBuf::from(format!(r"\\?\UNC\{}", share))
+    } else {
+        std::path::PathBuf::from(format!(r"\\?\{}", raw))
+    }
+}
 
-                let running = Arc::new(AtomicBool::new(true));
-                let running_clone = running.clone();
-                let app_for_thread = app_handle.clone();
+#[cfg(not(windows))]
+fn extended_length_path(path: &Path) -> std::path::PathBuf {
+    path.to_path_buf()
+}
 
-                // Fake progress thread for compression phase (50% -> 95%)
-                std::thread::spawn(move || {
-                    let mut progress: u8 = 50;
-                    let max_progress: u8 = 95;
-                    
-                    while running_clone.load(Ordering::SeqCst) && progress < max_progress {
-                        let _ = app_for_thread.emit("encryption_progress", progress);
-                        progress += 1;
-                        // Slow progress: 45% over ~22 seconds (500ms * 45)
-                        std::thread::sleep(Duration::from_millis(500));
-                    }
-                });
+// Temp folders and files of this app are named with this prefix and the process id, so
+// cleanup_temp can tell what a crashed run left behind from what's still in use
+const TEMP_PREFIX: &str = ".eazip-tmp-";
+// Another instance may still be working in its own; anything touched this recently stays
+const TEMP_CLEANUP_MIN_AGE: Duration = Duration::from_secs(60 * 60);
 
-                let res = sevenz_rust2::compress_to_path_encrypted(
-                    &temp_dir_path,
-                    &output_path,
-                    password.as_str().into(),
-                );
+fn temp_prefix() -> String {
+    format!("{}{}-", TEMP_PREFIX, std::process::id())
+}
+
+// Only the owner can look inside, what's staged there is often plaintext
+fn app_temp_dir() -> std::io::Result<tempfile::TempDir> {
+    let prefix = temp_prefix();
+    let mut builder = tempfile::Builder::new();
+    builder.prefix(&prefix);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        builder.permissions(fs::Permissions::from_mode(0o700));
+    }
+    builder.tempdir()
+}
 
-                running.store(false, Ordering::SeqCst);
-                res.map_err(|e| e.to_string())?;
+fn app_temp_file(suffix: &str) -> std::io::Result<tempfile::NamedTempFile> {
+    tempfile::Builder::new().prefix(&temp_prefix()).suffix(suffix).tempfile()
+}
 
-                app_handle.emit("encryption_progress", 100).unwrap(); // Stage 3: Compression complete
-                app_handle.emit("encryption_status", "Terminé !").unwrap();
+#[derive(Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TempCleanup {
+    removed: usize,
+    freed_bytes: u64,
+}
 
-                Ok(format!(
-                    "Files encrypted successfully to: {}",
-                    output_path
-                ))
+// Removes what earlier runs left in the temp folder after a crash: only names with
+// TEMP_PREFIX, never this process's own, and nothing modified in the last hour.
+// Whatever can't be removed is logged and left for next time.
+#[tauri::command]
+async fn cleanup_temp() -> Result<TempCleanup, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let own_prefix = temp_prefix();
+        let mut cleanup = TempCleanup::default();
+        for entry in fs::read_dir(std::env::temp_dir()).map_err(|e| e.to_string())?.flatten() {
+            let name = entry.file_name();
+            let Some(name) = name.to_str() else {
+                continue;
+            };
+            if !name.starts_with(TEMP_PREFIX) || name.starts_with(&own_prefix) {
+                continue;
+            }
+            let Ok(metadata) = entry.path().symlink_metadata() else {
+                continue;
+            };
+            let recent = metadata.modified().ok().and_then(|m| m.elapsed().ok()).map_or(true, |age| age < TEMP_CLEANUP_MIN_AGE);
+            if recent {
+                continue;
             }
-            _ => {
-                let output_path_buf = Path::new(&output_path);
-                let file = File::create(&output_path_buf)
-                    .map_err(|e| format!("Failed to create output file: {}", e))?;
-                let mut zip = ZipWriter::new(file);
-                
-                app_handle.emit("encryption_status", "Chiffrement en cours...").unwrap();
-
-                let options: FileOptions<'_, ()> = match encryption_method {
-                    EncryptionMethod::Aes256 => FileOptions::default()
-                        .compression_method(CompressionMethod::Deflated)
-                        .with_aes_encryption(AesMode::Aes256, &password),
-                    EncryptionMethod::CryptoZip => FileOptions::default()
-                        .compression_method(CompressionMethod::Deflated)
-                        .with_deprecated_encryption(password.as_bytes()),
-                    _ => unreachable!(),
-                };
 
-                let mut bytes_processed_total: u64 = 0;
-                let mut last_update_time = Instant::now();
-                let mut last_progress_percent: u8 = 0;
+            let path = entry.path();
+            let (size, removed) = if metadata.is_dir() {
+                let size = WalkDir::new(&path)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|e| e.metadata().ok())
+                    .filter(|m| m.is_file())
+                    .fold(0u64, |acc, m| acc.saturating_add(m.len()));
+                (size, fs::remove_dir_all(&path))
+            } else {
+                (metadata.len(), fs::remove_file(&path))
+            };
+            match removed {
+                Ok(()) => {
+                    cleanup.removed += 1;
+                    cleanup.freed_bytes = cleanup.freed_bytes.saturating_add(size);
+                }
+                Err(e) => log::warn!("Could not remove leftover {}: {}", path.display(), e),
+            }
+        }
+        Ok(cleanup)
+    }).await.map_err(|e| e.to_string())?
+}
 
-                for entry in &entries {
-                    if cancel_flag.load(Ordering::SeqCst) {
-                        let _ = std::fs::remove_file(&output_path_buf);
-                        return Err("Encryption cancelled by user.".to_string());
-                    }
+// OS error codes worth a hint: out of space (or inodes, reported the same way) and names
+// too long for the file system
+#[cfg(windows)]
+const STAGING_ERROR_CODES: (&[i32], &[i32]) = (&[39, 112], &[206]);
+#[cfg(target_os = "macos")]
+const STAGING_ERROR_CODES: (&[i32], &[i32]) = (&[28, 69], &[63]);
+#[cfg(all(unix, not(target_os = "macos")))]
+const STAGING_ERROR_CODES: (&[i32], &[i32]) = (&[28, 122], &[36]);
+#[cfg(not(any(windows, unix)))]
+const STAGING_ERROR_CODES: (&[i32], &[i32]) = (&[], &[]);
 
-                    let rel_str = entry.rel_path.to_str().ok_or("Invalid path encoding")?;
-
-                    if entry.is_dir {
-                        zip.add_directory(rel_str, options.clone())
-                           .map_err(|e| format!("Failed to add directory: {}", e))?;
-                    } else {
-                        zip.start_file(rel_str, options.clone())
-                            .map_err(|e| format!("Failed to start file in zip: {}", e))?;
-                        
-                        let mut f = File::open(&entry.abs_path)
-                            .map_err(|e| format!("Failed to open file: {}", e))?;
-                        
-                        let mut buffer = vec![0; 1024 * 1024]; // 1MB buffer
-                        loop {
-                            if cancel_flag.load(Ordering::SeqCst) {
-                                let _ = std::fs::remove_file(&output_path_buf);
-                                return Err("Encryption cancelled by user.".to_string());
-                            }
-                            let bytes_read = f
-                                .read(&mut buffer)
-                                .map_err(|e| format!("Failed to read file: {}", e))?;
-                            if bytes_read == 0 {
-                                break;
-                            }
-                            zip.write_all(&buffer[..bytes_read])
-                                .map_err(|e| format!("Failed to write to zip: {}", e))?;
-                            
-                            bytes_processed_total += bytes_read as u64;
-                            let progress = if total_size > 0 {
-                                (bytes_processed_total as f64 / total_size as f64 * 100.0) as u8
-                            } else {
-                                0
-                            };
-                            
-                            let now = Instant::now();
-                            if progress > last_progress_percent || now.duration_since(last_update_time) >= Duration::from_millis(100) {
-                                app_handle
-                                    .emit("encryption_progress", progress)
-                                    .map_err(|e| format!("Failed to emit progress event: {}", e))?;
-                                app_handle.emit("encryption_status", format!("Chiffrement: {}", entry.abs_path.file_name().and_then(|n| n.to_str()).unwrap_or("..."))).unwrap();
-                                last_update_time = now;
-                                last_progress_percent = progress;
-                            }
-                        }
-                    }
-                }
+// Errors from the 7z staging copy, with the paths involved and what to do about the
+// usual causes
+fn staging_error(context: String, e: std::io::Error) -> String {
+    let (no_space, too_long) = STAGING_ERROR_CODES;
+    let hint = match e.raw_os_error() {
+        Some(code) if no_space.contains(&code) => {
+            " (the temp folder is out of space or has too many files; free some space or point TMPDIR/TEMP to a larger drive)"
+        }
+        Some(code) if too_long.contains(&code) => {
+            " (path too long for the temp folder; shorten the folder structure or archive it as zip, which needs no temp copy)"
+        }
+        _ => "",
+    };
+    format!("{}: {}{}", context, e, hint)
+}
+
+// compress_to_path_encrypted with a configurable key derivation cost; entries are named
+// relative to `src_dir`, which isn't stored itself
+fn compress_7z_encrypted(
+    src_dir: &Path,
+    output_path: &str,
+    password: &str,
+    kdf_cycles_power: u8,
+    lzma2_level: Option<u32>,
+    sync_every_mb: Option<u64>,
+) -> Result<(), sevenz_rust2::Error> {
+    let mut writer = sevenz_rust2::ArchiveWriter::new(create_output(Path::new(output_path), sync_every_mb)?)?;
+    let lzma2: sevenz_rust2::EncoderConfiguration = match lzma2_level {
+        Some(level) => LZMA2Options::from_level(level).into(),
+        None => sevenz_rust2::EncoderMethod::LZMA2.into(),
+    };
+    if !password.is_empty() {
+        writer.set_content_methods(vec![seven_zip_aes_options(password, kdf_cycles_power).into(), lzma2]);
+    } else if lzma2_level.is_some() {
+        writer.set_content_methods(vec![lzma2]);
+    }
+    for entry in WalkDir::new(src_dir).min_depth(1) {
+        let entry = entry.map_err(std::io::Error::from)?;
+        let name = entry.path().strip_prefix(src_dir).unwrap_or(entry.path()).to_string_lossy().into_owned();
+        let archive_entry = sevenz_rust2::ArchiveEntry::from_path(entry.path(), name);
+        if entry.file_type().is_dir() {
+            writer.push_archive_entry::<&[u8]>(archive_entry, None)?;
+        } else if entry.file_type().is_file() {
+            writer.push_archive_entry(archive_entry, Some(File::open(entry.path())?))?;
+        }
+    }
+    finish_output(writer.finish()?)?;
+    Ok(())
+}
 
-                zip.finish()
-                    .map_err(|e| format!("Failed to finish zip: {}", e))?;
+// Start of the staged files compressed up front to time the 7z phase
+const SEVEN_ZIP_CALIBRATION_SAMPLE: u64 = 4 * 1024 * 1024;
 
-                Ok(format!(
-                    "Files encrypted successfully to: {}",
-                    output_path_buf.display()
-                ))
-            }
+// How long the 7z writer should take for `total_size` bytes, extrapolated from LZMA2 on
+// a sample. AES and the one-off key derivation are left out, both cost little next to
+// LZMA2. None when there's nothing to sample.
+fn estimate_7z_duration(src_dir: &Path, total_size: u64) -> Option<Duration> {
+    let mut sample = Vec::new();
+    for entry in WalkDir::new(src_dir).sort_by_file_name().into_iter().filter_map(Result::ok) {
+        let remaining = SEVEN_ZIP_CALIBRATION_SAMPLE - sample.len() as u64;
+        if remaining == 0 {
+            break;
         }
-    }).await.map_err(|e| e.to_string())?
+        if entry.file_type().is_file() {
+            let _ = File::open(entry.path()).and_then(|f| f.take(remaining).read_to_end(&mut sample));
+        }
+    }
+    if sample.is_empty() {
+        return None;
+    }
+
+    let started = Instant::now();
+    let mut writer = sevenz_rust2::ArchiveWriter::new(std::io::Cursor::new(Vec::new())).ok()?;
+    writer.push_archive_entry(sevenz_rust2::ArchiveEntry::new_file("sample"), Some(sample.as_slice())).ok()?;
+    writer.finish().ok()?;
+    let bytes_per_sec = sample.len() as f64 / started.elapsed().as_secs_f64().max(1e-6);
+    Some(Duration::from_secs_f64(total_size as f64 / bytes_per_sec))
 }
 
-#[tauri::command]
-async fn decrypt_file(
-    app_handle: tauri::AppHandle,
-    state: tauri::State<'_, AppState>,
-    file_path: String,
-    output_dir: String,
-    password: Secret<String>,
-) -> Result<String, String> {
-    const MAX_TOTAL_SIZE: u64 = 10 * 1024 * 1024 * 1024; // 10 GB
-    const MAX_FILE_COUNT: usize = 10_000;
+// "45 s", "3 min 20 s", "1 h 05 min" for the status line
+fn format_remaining(remaining: Duration) -> String {
+    let secs = remaining.as_secs().max(1);
+    match secs {
+        0..=59 => format!("{} s", secs),
+        60..=3599 => format!("{} min {:02} s", secs / 60, secs % 60),
+        _ => format!("{} h {:02} min", secs / 3600, secs % 3600 / 60),
+    }
+}
 
-    let cancel_flag = state.cancel_flag.clone();
-    let password = password.expose_secret().clone();
+// Archives go out through a large buffer so a high-latency target (NAS, SMB or NFS
+// share) sees a few big writes instead of many small round trips
+const OUTPUT_BUFFER_SIZE: usize = 4 * 1024 * 1024;
 
-    tauri::async_runtime::spawn_blocking(move || {
-        cancel_flag.store(false, Ordering::SeqCst);
-        
-        let path = Path::new(&file_path);
-        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+fn create_output(path: &Path, sync_every_mb: Option<u64>) -> std::io::Result<BufWriter<OutputFile>> {
+    let output = OutputFile {
+        file: File::create(path)?,
+        path: path.to_path_buf(),
+        sync_every: sync_every_mb.map(|mb| mb.saturating_mul(1024 * 1024)),
+        unsynced: 0,
+    };
+    Ok(BufWriter::with_capacity(OUTPUT_BUFFER_SIZE, output))
+}
 
-        if extension == "7z" {
-            app_handle.emit("encryption_status", "Déchiffrement 7z en cours...").unwrap();
-            
-            let running = Arc::new(AtomicBool::new(true));
-            let running_clone = running.clone();
-            let app_for_thread = app_handle.clone();
-            
-            // Fake progress thread
-            std::thread::spawn(move || {
-                let mut progress: u8 = 0;
-                let max_progress: u8 = 95;
-                
-                while running_clone.load(Ordering::SeqCst) && progress < max_progress {
-                    let _ = app_for_thread.emit("encryption_progress", progress);
-                    progress += 1;
-                    // Slow progress: 95% over ~47 seconds (500ms * 95)
-                    // Adjust sleep to make it faster or slower depending on expected size
-                    std::thread::sleep(Duration::from_millis(500));
-                }
-            });
+// Archive file that syncs its data to disk every `sync_every` bytes written
+struct OutputFile {
+    file: File,
+    path: std::path::PathBuf,
+    sync_every: Option<u64>,
+    unsynced: u64,
+}
 
-            let res = sevenz_rust2::decompress_file_with_password(
-                path,
-                &output_dir,
-                password.as_str().into(),
-            );
-            
-            running.store(false, Ordering::SeqCst);
-            res.map_err(|e| e.to_string())?;
-        } else {
-            app_handle.emit("encryption_status", "Ouverture de l'archive...").unwrap();
-            let file = File::open(&path).map_err(|e| e.to_string())?;
-            let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+impl Write for OutputFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.file.write(buf)?;
+        self.unsynced += written as u64;
+        if self.sync_every.is_some_and(|every| self.unsynced >= every) {
+            self.file.sync_data()?;
+            self.unsynced = 0;
+        }
+        Ok(written)
+    }
 
-            app_handle.emit("encryption_status", "Calcul de la taille totale...").unwrap();
-            // Calculate total size for progress
-            let mut total_size: u64 = 0;
-            let len = archive.len();
-            for i in 0..len {
-                if cancel_flag.load(Ordering::SeqCst) {
-                    return Err("Decryption cancelled by user.".to_string());
-                }
-                if i % 50 == 0 {
-                    app_handle.emit("encryption_status", format!("Analyse du contenu... ({}/{})", i, len)).unwrap();
-                }
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
 
-                // We must use by_index_decrypt even for size calculation if the file is encrypted
-                let file = archive.by_index_decrypt(i, password.as_bytes()).map_err(|e| e.to_string())?;
-                total_size += file.size();
-            }
+impl Seek for OutputFile {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.file.seek(pos)
+    }
+}
 
-            let mut total_extracted_size: u64 = 0;
-            let mut extracted_count: usize = 0;
-            let mut last_update_time = Instant::now();
-            let mut last_progress_percent: u8 = 0;
+// Dropping a BufWriter swallows the last write error and network filesystems often
+// only report failures on flush or close, so both are checked explicitly. The parent
+// folder is synced too so the new file's directory entry survives a power loss.
+fn finish_output(writer: BufWriter<OutputFile>) -> std::io::Result<()> {
+    let output = writer.into_inner().map_err(|e| e.into_error())?;
+    output.file.sync_all()?;
+    #[cfg(unix)]
+    if let Some(parent) = output.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        // Some filesystems refuse fsync on a directory, the file itself is already safe
+        let _ = File::open(parent).and_then(|dir| dir.sync_all());
+    }
+    Ok(())
+}
 
-            app_handle.emit("encryption_status", "Déchiffrement en cours...").unwrap();
+// An .eazip container is a plain zip encrypted as a whole with AES-256-GCM. The 37-byte
+// header (integers little-endian) is:
+//   magic "EAZC" | version u8 = 1 | kdf u8 = 1 (PBKDF2-HMAC-SHA256) | iterations u32
+//   | salt [16] | nonce prefix [7] | chunk size u32
+// followed by the zip cut into chunks of `chunk size` bytes, each sealed on its own and
+// stored with its 16-byte tag. The last chunk is always shorter than the others, empty
+// if need be. Chunk n's nonce is the prefix, n as u32 big-endian, then 1 for the last
+// chunk or 0; every chunk authenticates the header as associated data, so editing the
+// header, reordering, dropping or truncating chunks all fail to decrypt.
+const CONTAINER_MAGIC: &[u8; 4] = b"EAZC";
+const CONTAINER_VERSION: u8 = 1;
+const CONTAINER_KDF_PBKDF2_SHA256: u8 = 1;
+const CONTAINER_HEADER_LEN: usize = 37;
+const CONTAINER_TAG_LEN: usize = 16;
+const CONTAINER_ITERATIONS: u32 = 600_000;
+const CONTAINER_CHUNK_SIZE: u32 = 1024 * 1024;
+// Limits on what a header may ask for, so a crafted file can't pin the CPU or memory
+const CONTAINER_MAX_ITERATIONS: u32 = 10_000_000;
+const CONTAINER_MAX_CHUNK_SIZE: u32 = 64 * 1024 * 1024;
 
-            for i in 0..archive.len() {
-                if cancel_flag.load(Ordering::SeqCst) {
-                    return Err("Decryption cancelled by user.".to_string());
-                }
+struct ContainerHeader {
+    iterations: u32,
+    salt: [u8; 16],
+    nonce_prefix: [u8; 7],
+    chunk_size: u32,
+}
 
-                let mut file = archive
-                    .by_index_decrypt(i, password.as_bytes())
-                    .map_err(|e| {
-                        if let zip::result::ZipError::InvalidPassword = e {
-                            "Mot de passe incorrect".to_string()
-                        } else {
-                            e.to_string()
-                        }
-                    })?;
-                
-                // Zip Bomb Protection
-                extracted_count += 1;
-                if extracted_count > MAX_FILE_COUNT {
-                    return Err(format!("Too many files in archive (limit: {})", MAX_FILE_COUNT));
-                }
+impl ContainerHeader {
+    fn generate() -> Self {
+        let mut rng = rand::thread_rng();
+        ContainerHeader {
+            iterations: CONTAINER_ITERATIONS,
+            salt: rng.gen(),
+            nonce_prefix: rng.gen(),
+            chunk_size: CONTAINER_CHUNK_SIZE,
+        }
+    }
 
-                let size = file.size();
-                // We check total_extracted_size dynamically as we write, but checking here is good too
-                if total_extracted_size + size > MAX_TOTAL_SIZE {
-                     return Err(format!("Total extracted size exceeds limit (limit: {} bytes)", MAX_TOTAL_SIZE));
-                }
+    fn to_bytes(&self) -> [u8; CONTAINER_HEADER_LEN] {
+        let mut bytes = [0u8; CONTAINER_HEADER_LEN];
+        bytes[..4].copy_from_slice(CONTAINER_MAGIC);
+        bytes[4] = CONTAINER_VERSION;
+        bytes[5] = CONTAINER_KDF_PBKDF2_SHA256;
+        bytes[6..10].copy_from_slice(&self.iterations.to_le_bytes());
+        bytes[10..26].copy_from_slice(&self.salt);
+        bytes[26..33].copy_from_slice(&self.nonce_prefix);
+        bytes[33..].copy_from_slice(&self.chunk_size.to_le_bytes());
+        bytes
+    }
 
-                // Zip Slip Protection
-                let outpath = Path::new(&output_dir).join(file.mangled_name());
-                let canonical_output_dir = Path::new(&output_dir).canonicalize().map_err(|e| e.to_string())?;
-                
-                if !outpath.starts_with(&output_dir) {
-                     return Err("Invalid file path (Zip Slip attempt detected)".to_string());
-                }
+    fn parse(bytes: &[u8; CONTAINER_HEADER_LEN]) -> Result<Self, String> {
+        if bytes[..4] != *CONTAINER_MAGIC {
+            return Err("Not an EaZip container".to_string());
+        }
+        if bytes[4] != CONTAINER_VERSION || bytes[5] != CONTAINER_KDF_PBKDF2_SHA256 {
+            return Err(format!("Unsupported container version {} (key derivation {})", bytes[4], bytes[5]));
+        }
+        let u32_at = |i: usize| u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        let header = ContainerHeader {
+            iterations: u32_at(6),
+            salt: bytes[10..26].try_into().unwrap(),
+            nonce_prefix: bytes[26..33].try_into().unwrap(),
+            chunk_size: u32_at(33),
+        };
+        if header.iterations == 0 || header.iterations > CONTAINER_MAX_ITERATIONS {
+            return Err("Container key derivation cost out of range".to_string());
+        }
+        if header.chunk_size == 0 || header.chunk_size > CONTAINER_MAX_CHUNK_SIZE {
+            return Err("Container chunk size out of range".to_string());
+        }
+        Ok(header)
+    }
 
-                if file.is_dir() {
-                    fs::create_dir_all(&outpath).map_err(|e| e.to_string())?;
-                } else {
-                    if let Some(p) = outpath.parent() {
-                        if !p.exists() {
-                            fs::create_dir_all(p).map_err(|e| e.to_string())?;
-                        }
-                        let canonical_parent = p.canonicalize().map_err(|e| e.to_string())?;
-                        if !canonical_parent.starts_with(&canonical_output_dir) {
-                             return Err("Invalid file path (Zip Slip attempt detected)".to_string());
-                        }
-                    }
-                    
-                    let mut outfile = File::create(&outpath).map_err(|e| e.to_string())?;
-                    
-                    // Manual copy with progress
-                    let mut buffer = vec![0; 1024 * 1024]; // 1MB buffer
-                    loop {
-                        if cancel_flag.load(Ordering::SeqCst) {
-                            return Err("Decryption cancelled by user.".to_string());
-                        }
-                        let bytes_read = file.read(&mut buffer).map_err(|e| e.to_string())?;
-                        if bytes_read == 0 {
-                            break;
-                        }
-                        outfile.write_all(&buffer[..bytes_read]).map_err(|e| e.to_string())?;
-                        
-                        total_extracted_size += bytes_read as u64;
-                        
-                        let progress = if total_size > 0 {
-                            (total_extracted_size as f64 / total_size as f64 * 100.0) as u8
-                        } else {
-                            0
-                        };
+    fn cipher(&self, password: &str) -> Aes256Gcm {
+        let mut key = [0u8; 32];
+        pbkdf2::pbkdf2_hmac::<sha2::Sha256>(password.as_bytes(), &self.salt, self.iterations, &mut key);
+        Aes256Gcm::new(&key.into())
+    }
 
-                        let now = Instant::now();
-                        if progress > last_progress_percent || now.duration_since(last_update_time) >= Duration::from_millis(100) {
-                            app_handle.emit("encryption_progress", progress).unwrap();
-                            // Optional: emit filename status if desired, but might be too fast
-                            // app_handle.emit("encryption_status", format!("Extraction: {}", file.name())).unwrap();
-                            last_update_time = now;
-                            last_progress_percent = progress;
-                        }
-                    }
-                }
-            }
+    fn nonce(&self, index: u32, last: bool) -> [u8; 12] {
+        let mut nonce = [0u8; 12];
+        nonce[..7].copy_from_slice(&self.nonce_prefix);
+        nonce[7..11].copy_from_slice(&index.to_be_bytes());
+        nonce[11] = last as u8;
+        nonce
+    }
+}
+
+fn is_container(path: &Path) -> bool {
+    let mut magic = [0u8; 4];
+    File::open(path).and_then(|mut f| f.read_exact(&mut magic)).is_ok() && magic == *CONTAINER_MAGIC
+}
+
+// read_exact that stops at end of file, returning how much was read
+fn read_up_to(reader: &mut impl Read, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
         }
+    }
+    Ok(filled)
+}
 
-        app_handle.emit("encryption_progress", 100).unwrap();
-        app_handle.emit("encryption_status", "Déchiffrement terminé !").unwrap();
+fn seal_container(
+    cancel_flag: &AtomicBool,
+    input: &Path,
+    output: &Path,
+    password: &str,
+    sync_every_mb: Option<u64>,
+) -> Result<(), String> {
+    let header = ContainerHeader::generate();
+    let header_bytes = header.to_bytes();
+    let cipher = header.cipher(password);
+    let mut reader = File::open(input).map_err(|e| e.to_string())?;
+    let mut remaining = reader.metadata().map_err(|e| e.to_string())?.len();
+    let mut writer = create_output(output, sync_every_mb).map_err(|e| format!("Failed to create output file: {}", e))?;
+    writer.write_all(&header_bytes).map_err(|e| e.to_string())?;
 
-        Ok(format!("File decrypted successfully to: {}", output_dir))
-    }).await.map_err(|e| e.to_string())?
+    let mut chunk = vec![0u8; header.chunk_size as usize];
+    let mut index: u32 = 0;
+    loop {
+        if cancel_flag.load(Ordering::SeqCst) {
+            return Err("Encryption cancelled by user.".to_string());
+        }
+        let last = remaining < header.chunk_size as u64;
+        let len = remaining.min(header.chunk_size as u64) as usize;
+        reader.read_exact(&mut chunk[..len]).map_err(|e| e.to_string())?;
+        remaining -= len as u64;
+        let sealed = cipher
+            .encrypt(&header.nonce(index, last).into(), Payload { msg: &chunk[..len], aad: &header_bytes })
+            .map_err(|_| "Failed to encrypt the archive".to_string())?;
+        writer.write_all(&sealed).map_err(|e| format!("Failed to write {}: {}", output.display(), e))?;
+        if last {
+            break;
+        }
+        index = index.checked_add(1).ok_or("Archive too large for a container")?;
+    }
+    finish_output(writer).map_err(|e| format!("Failed to write {}: {}", output.display(), e))
 }
 
-#[tauri::command]
-fn cancel_encryption(state: tauri::State<'_, AppState>) {
-    state.cancel_flag.store(true, Ordering::SeqCst);
+// Decrypts the container into a temp zip. A wrong password and a modified first chunk or
+// header look the same, a failed tag; once a chunk has opened the password is known good,
+// so any later failure is reported as tampering
+fn open_container<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    cancel_flag: &AtomicBool,
+    path: &Path,
+    password: &str,
+) -> Result<tempfile::NamedTempFile, String> {
+    emit_status(app_handle, "Déchiffrement du conteneur...").map_err(|e| e.to_string())?;
+    let mut reader = std::io::BufReader::new(File::open(path).map_err(|e| e.to_string())?);
+    let mut header_bytes = [0u8; CONTAINER_HEADER_LEN];
+    reader.read_exact(&mut header_bytes).map_err(|_| "Truncated container header".to_string())?;
+    let header = ContainerHeader::parse(&header_bytes)?;
+    let cipher = header.cipher(password);
+
+    let mut opened = app_temp_file(".zip").map_err(|e| e.to_string())?;
+    let mut chunk = vec![0u8; header.chunk_size as usize + CONTAINER_TAG_LEN];
+    let mut index: u32 = 0;
+    loop {
+        if cancel_flag.load(Ordering::SeqCst) {
+            return Err("Decryption cancelled by user.".to_string());
+        }
+        let len = read_up_to(&mut reader, &mut chunk).map_err(|e| e.to_string())?;
+        // Only the last chunk is short, a full one always has more after it
+        let last = len < chunk.len();
+        let plain = cipher
+            .decrypt(&header.nonce(index, last).into(), Payload { msg: &chunk[..len], aad: &header_bytes })
+            .map_err(|_| match index {
+                0 => "Wrong password, or the container was modified".to_string(),
+                _ => format!("Authentication failed: the container was modified (chunk {})", index),
+            })?;
+        opened.write_all(&plain).map_err(|e| e.to_string())?;
+        if last {
+            break;
+        }
+        index = index.checked_add(1).ok_or("Corrupted container")?;
+    }
+    Ok(opened)
 }
 
-fn main() {
-    let app_state = AppState {
-        cancel_flag: Arc::new(AtomicBool::new(false)),
-    };
+fn wildcard_match(pattern: &[u8], name: &[u8]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => {
+            wildcard_match(&pattern[1..], name) || (!name.is_empty() && wildcard_match(pattern, &name[1..]))
+        }
+        (Some(b'?'), Some(_)) => wildcard_match(&pattern[1..], &name[1..]),
+        (Some(p), Some(n)) if p == n => wildcard_match(&pattern[1..], &name[1..]),
+        _ => false,
+    }
+}
 
-    tauri::Builder::default()
-        .plugin(tauri_plugin_dialog::init())
-        .plugin(tauri_plugin_log::Builder::default().build())
-        .manage(app_state)
-        .invoke_handler(tauri::generate_handler![
-            generate_password,
-            encrypt_files,
-            decrypt_file,
-            cancel_encryption,
-            get_file_metadata
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+// Patterns without a slash match the file name anywhere in the tree, others the full relative path
+fn matches_any_pattern(patterns: &[String], rel_path: &str) -> bool {
+    let file_name = rel_path.rsplit('/').next().unwrap_or(rel_path);
+    patterns.iter().any(|pattern| {
+        let target = if pattern.contains('/') { rel_path } else { file_name };
+        wildcard_match(pattern.as_bytes(), target.as_bytes())
+    })
+}
+
+// Write sink feeding the selected digest, so hashing can go through copy_with_progress
+enum ChecksumHasher {
+    Sha256(sha2::Sha256),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl ChecksumHasher {
+    fn new(algorithm: ChecksumAlgorithm) -> Self {
+        use sha2::Digest;
+        match algorithm {
+            ChecksumAlgorithm::Sha256 => ChecksumHasher::Sha256(sha2::Sha256::new()),
+            ChecksumAlgorithm::Blake3 => ChecksumHasher::Blake3(Box::new(blake3::Hasher::new())),
+        }
+    }
+
+    fn finalize_hex(self) -> String {
+        use sha2::Digest;
+        match self {
+            ChecksumHasher::Sha256(hasher) => hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect(),
+            ChecksumHasher::Blake3(hasher) => hasher.finalize().to_hex().to_string(),
+        }
+    }
+}
+
+impl Write for ChecksumHasher {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        use sha2::Digest;
+        match self {
+            ChecksumHasher::Sha256(hasher) => hasher.update(buf),
+            ChecksumHasher::Blake3(hasher) => {
+                hasher.update(buf);
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn hash_file(
+    path: &Path,
+    algorithm: ChecksumAlgorithm,
+    cancel_flag: &AtomicBool,
+    progress: &mut ProgressTracker<impl tauri::Runtime>,
+    cancelled_message: &str,
+) -> Result<String, String> {
+    let mut f = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut hasher = ChecksumHasher::new(algorithm);
+    copy_with_progress(&mut f, &mut hasher, cancel_flag, progress, cancelled_message)?;
+    Ok(hasher.finalize_hex())
+}
+
+fn write_checksum_sidecar<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    cancel_flag: &AtomicBool,
+    archive: &Path,
+    algorithm: ChecksumAlgorithm,
+) -> Result<(), String> {
+    emit_status(app_handle, "Calcul de la somme de contrôle...").map_err(|e| e.to_string())?;
+    let size = fs::metadata(archive).map_err(|e| format!("Failed to hash archive: {}", e))?.len();
+    let mut progress = ProgressTracker::new(app_handle, size);
+    let hash = hash_file(archive, algorithm, cancel_flag, &mut progress, "Encryption cancelled by user.")?;
+    emit_progress(app_handle, 100).map_err(|e| e.to_string())?;
+    let extension = match algorithm {
+        ChecksumAlgorithm::Sha256 => "sha256",
+        ChecksumAlgorithm::Blake3 => "b3",
+    };
+    // The file name only, so the check still works once both files are moved together
+    let file_name = archive.file_name().ok_or("Invalid archive path")?.to_string_lossy();
+    let mut sidecar = archive.as_os_str().to_owned();
+    sidecar.push(format!(".{}", extension));
+    fs::write(&sidecar, format!("{}  {}\n", hash, file_name))
+        .map_err(|e| format!("Failed to write checksum file: {}", e))
+}
+
+// Same time whichever byte differs first
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// Both algorithms give 32-byte digests. A pasted `<hash>  <filename>` line works too,
+// only the first field is used.
+// Cancelled with cancel_decryption, checking an archive usually comes right before opening it
+#[tauri::command]
+async fn verify_file_checksum(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    path: String,
+    expected_hex: String,
+    algo: ChecksumAlgorithm,
+    job_id: Option<JobId>,
+) -> Result<bool, String> {
+    let expected = expected_hex.split_whitespace().next().unwrap_or_default().to_ascii_lowercase();
+    if expected.len() != 64 || !expected.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err("Expected checksum must be 64 hexadecimal characters".to_string());
+    }
+
+    let job = state.jobs.start(JobKind::Decrypt, job_id)?;
+    let cancel_flag = job.cancel_flag();
+    tauri::async_runtime::spawn_blocking(move || {
+        let _job = job.enter();
+        let path = Path::new(&path);
+        let size = fs::metadata(path).map_err(|e| e.to_string())?.len();
+        emit_status(&app_handle, "Vérification de la somme de contrôle...").map_err(|e| e.to_string())?;
+        let mut progress = ProgressTracker::new(&app_handle, size);
+        let computed = hash_file(path, algo, &cancel_flag, &mut progress, "Checksum verification cancelled by user.")?;
+        emit_progress(&app_handle, 100).map_err(|e| e.to_string())?;
+
+        if !constant_time_eq(computed.as_bytes(), expected.as_bytes()) {
+            return Err(format!("Checksum mismatch: expected {}, computed {}", expected, computed));
+        }
+        Ok(true)
+    }).await.map_err(|e| e.to_string())?
+}
+
+fn file_crc32(path: &Path) -> std::io::Result<u32> {
+    reader_crc32(&mut File::open(path)?)
+}
+
+fn reader_crc32(f: &mut impl Read) -> std::io::Result<u32> {
+    let mut hasher = crc32fast::Hasher::new();
+    let mut buffer = vec![0; COPY_BUFFER_SIZE];
+    loop {
+        let bytes_read = f.read(&mut buffer)?;
+        if bytes_read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..bytes_read]);
+    }
+    Ok(hasher.finalize())
+}
+
+// An output file counts as done when its size matches the entry and, when the
+// archive stores one (AES entries don't), its CRC matches too
+fn is_already_extracted(path: &Path, size: u64, crc32: u32) -> bool {
+    match fs::metadata(path) {
+        Ok(meta) if meta.is_file() && meta.len() == size => {
+            crc32 == 0 || file_crc32(path).map(|c| c == crc32).unwrap_or(false)
+        }
+        _ => false,
+    }
+}
+
+fn read_entry_stats(path: &Path, password: &str) -> Result<Vec<EntryStats>, String> {
+    if is_7z_path(path) {
+        let archive = sevenz_rust2::Archive::open_with_password(path, &password.into())
+            .map_err(|e| e.to_string())?;
+        return Ok(archive
+            .files
+            .iter()
+            .filter(|f| !f.is_directory())
+            .map(|f| EntryStats {
+                name: f.name().to_string(),
+                original_size: f.size(),
+                compressed_size: f.compressed_size,
+            })
+            .collect());
+    }
+
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+    let mut stats = Vec::with_capacity(archive.len());
+    for i in 0..archive.len() {
+        let f = archive.by_index_raw(i).map_err(|e| e.to_string())?;
+        if f.is_dir() {
+            continue;
+        }
+        stats.push(EntryStats {
+            name: f.name().to_string(),
+            original_size: f.size(),
+            compressed_size: f.compressed_size(),
+        });
+    }
+    Ok(stats)
+}
+
+// Throttled "encryption_progress" emitter shared by the chunked copy loops
+struct ProgressTracker<'a, R: tauri::Runtime> {
+    app_handle: &'a tauri::AppHandle<R>,
+    total: u64,
+    done: u64,
+    last_update_time: Instant,
+    last_progress_percent: u8,
+    // When set, percentages follow finished entries instead of bytes
+    entry_total: Option<usize>,
+    entries_done: usize,
+    // Last raw percentages, averaged so the bar doesn't jump around
+    recent: std::collections::VecDeque<f64>,
+}
+
+const PROGRESS_SMOOTHING_WINDOW: usize = 5;
+
+impl<'a, R: tauri::Runtime> ProgressTracker<'a, R> {
+    fn new(app_handle: &'a tauri::AppHandle<R>, total: u64) -> Self {
+        ProgressTracker {
+            app_handle,
+            total,
+            done: 0,
+            last_update_time: Instant::now(),
+            last_progress_percent: 0,
+            entry_total: None,
+            entries_done: 0,
+            recent: std::collections::VecDeque::with_capacity(PROGRESS_SMOOTHING_WINDOW),
+        }
+    }
+
+    fn by_entries(app_handle: &'a tauri::AppHandle<R>, entry_total: usize) -> Self {
+        ProgressTracker {
+            entry_total: Some(entry_total),
+            ..ProgressTracker::new(app_handle, 0)
+        }
+    }
+
+    // A selection of empty files and directories adds up to 0 bytes, the bar
+    // would never move, so it follows the entry count instead
+    fn bytes_or_entries(app_handle: &'a tauri::AppHandle<R>, total: u64, entry_total: usize) -> Self {
+        if total > 0 {
+            ProgressTracker::new(app_handle, total)
+        } else {
+            ProgressTracker::by_entries(app_handle, entry_total)
+        }
+    }
+
+    fn finish_entry(&mut self) {
+        self.entries_done += 1;
+        if self.entry_total.is_some() {
+            self.advance(0);
+        }
+    }
+
+    // Returns whether an event went out, so callers can piggyback status updates
+    fn advance(&mut self, bytes: u64) -> bool {
+        self.done += bytes;
+
+        let raw = match self.entry_total {
+            Some(entry_total) if entry_total > 0 => self.entries_done as f64 / entry_total as f64 * 100.0,
+            Some(_) => 0.0,
+            None if self.total > 0 => self.done as f64 / self.total as f64 * 100.0,
+            None => 0.0,
+        };
+        // `total` is only an estimate (files can grow while being read), never go past 100
+        if self.recent.len() == PROGRESS_SMOOTHING_WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(raw.min(100.0));
+        let smoothed = self.recent.iter().sum::<f64>() / self.recent.len() as f64;
+        // The bar never moves backwards
+        let progress = (smoothed as u8).max(self.last_progress_percent);
+
+        let now = Instant::now();
+        if progress > self.last_progress_percent || now.duration_since(self.last_update_time) >= PROGRESS_INTERVAL {
+            // A lost progress event isn't worth stopping the job for
+            if let Err(e) = emit_progress(self.app_handle, progress) {
+                log::warn!("Could not send progress event: {}", e);
+            }
+            if self.entry_total.is_none() {
+                if let Err(e) = emit_progress_bytes(self.app_handle, self.done.min(self.total), self.total) {
+                    log::warn!("Could not send progress event: {}", e);
+                }
+            }
+            self.last_update_time = now;
+            self.last_progress_percent = progress;
+            return true;
+        }
+        false
+    }
+}
+
+// Copies in 1MB chunks, checking the cancel flag and reporting progress between chunks
+fn copy_with_progress<R: Read + ?Sized, W: Write + ?Sized>(
+    reader: &mut R,
+    writer: &mut W,
+    cancel_flag: &AtomicBool,
+    progress: &mut ProgressTracker<impl tauri::Runtime>,
+    cancelled_message: &str,
+) -> Result<u64, String> {
+    let mut buffer = vec![0; COPY_BUFFER_SIZE];
+    let mut copied: u64 = 0;
+    loop {
+        if cancel_flag.load(Ordering::SeqCst) {
+            return Err(cancelled_message.to_string());
+        }
+        let bytes_read = reader.read(&mut buffer).map_err(|e| e.to_string())?;
+        if bytes_read == 0 {
+            break;
+        }
+        writer.write_all(&buffer[..bytes_read]).map_err(|e| e.to_string())?;
+
+        copied += bytes_read as u64;
+        progress.advance(bytes_read as u64);
+    }
+    Ok(copied)
+}
+
+// Resolves a 7z/RAR entry name under output_dir, refusing anything that could escape it
+fn safe_entry_output_path(output_dir: &Path, entry_name: &str) -> Result<std::path::PathBuf, String> {
+    let normalized = entry_name.replace('\\', "/");
+    let mut outpath = output_dir.to_path_buf();
+    for component in Path::new(&normalized).components() {
+        match component {
+            std::path::Component::Normal(part) => outpath.push(part),
+            std::path::Component::CurDir => {}
+            _ => return Err("Invalid file path (Zip Slip attempt detected)".to_string()),
+        }
+    }
+    Ok(outpath)
+}
+
+// `dir/**` moves everything under `dir` to the replacement folder, keeping the rest of
+// the path; any other pattern is matched against the whole path and sends matching
+// entries straight into the replacement folder. The first matching rule wins and the
+// result goes through the same checks as an entry name.
+fn map_output_path(
+    output_dir: &Path,
+    outpath: std::path::PathBuf,
+    path_map: &[(String, String)],
+) -> Result<std::path::PathBuf, String> {
+    let Ok(rel) = outpath.strip_prefix(output_dir) else {
+        return Ok(outpath);
+    };
+    let rel = rel.to_string_lossy().replace('\\', "/");
+    let join = |replacement: &str, rest: &str| match (replacement.trim_end_matches('/'), rest) {
+        ("", rest) => rest.to_string(),
+        (replacement, "") => replacement.to_string(),
+        (replacement, rest) => format!("{}/{}", replacement, rest),
+    };
+    for (pattern, replacement) in path_map {
+        let mapped = match pattern.strip_suffix("/**") {
+            Some(prefix) => {
+                let depth = prefix.split('/').count();
+                let mut parts = rel.splitn(depth + 1, '/');
+                let head = parts.by_ref().take(depth).collect::<Vec<_>>().join("/");
+                if head.split('/').count() < depth || !wildcard_match(prefix.as_bytes(), head.as_bytes()) {
+                    continue;
+                }
+                join(replacement, parts.next().unwrap_or(""))
+            }
+            None if wildcard_match(pattern.as_bytes(), rel.as_bytes()) => {
+                join(replacement, rel.rsplit('/').next().unwrap_or(&rel))
+            }
+            None => continue,
+        };
+        return safe_entry_output_path(output_dir, &mapped);
+    }
+    Ok(outpath)
+}
+
+// None keeps the zip crate's own CP437 decoding, which encoding_rs doesn't provide
+fn legacy_name_encoding(label: Option<&str>) -> Result<Option<&'static encoding_rs::Encoding>, String> {
+    match label.map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(label) if matches!(label.to_ascii_lowercase().as_str(), "cp437" | "ibm437" | "437") => Ok(None),
+        Some(label) => encoding_rs::Encoding::for_label(label.as_bytes())
+            .map(Some)
+            .ok_or_else(|| format!("Unsupported encoding: {}", label)),
+    }
+}
+
+// Name stored without the UTF-8 flag and without a Unicode path extra field: the zip
+// crate then decoded it byte by byte as CP437
+fn is_legacy_encoded_name(file: &zip::read::ZipFile) -> bool {
+    let raw = file.name_raw();
+    !raw.is_ascii()
+        && std::str::from_utf8(raw).map_or(true, |name| name != file.name())
+        && file.name().chars().count() == raw.len()
+}
+
+fn is_unsafe_entry_name(name: &str) -> bool {
+    let normalized = name.replace('\\', "/");
+    let bytes = normalized.as_bytes();
+    let has_drive = bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+    normalized.starts_with('/') || has_drive || normalized.split('/').any(|part| part == "..")
+}
+
+// Tracks extracted names to catch entries that only differ by case, which would
+// silently overwrite each other on case-insensitive filesystems (macOS, Windows)
+struct CaseCollisions {
+    enabled: bool,
+    seen: std::collections::HashSet<String>,
+}
+
+impl CaseCollisions {
+    fn new(output_dir: &Path) -> Self {
+        // Probe the target itself, the answer can differ per volume
+        let enabled = tempfile::Builder::new()
+            .prefix(".eazip-case-probe")
+            .tempfile_in(output_dir)
+            .ok()
+            .and_then(|probe| {
+                let name = probe.path().file_name()?.to_str()?.to_uppercase();
+                Some(output_dir.join(name).exists())
+            })
+            .unwrap_or(false);
+
+        CaseCollisions {
+            enabled,
+            seen: std::collections::HashSet::new(),
+        }
+    }
+
+    // Returns where the entry should be written, or None to skip it
+    fn resolve<R: tauri::Runtime>(
+        &mut self,
+        app_handle: &tauri::AppHandle<R>,
+        outpath: std::path::PathBuf,
+        policy: CollisionPolicy,
+    ) -> Option<std::path::PathBuf> {
+        if !self.enabled {
+            return Some(outpath);
+        }
+        if self.seen.insert(outpath.to_string_lossy().to_lowercase()) {
+            return Some(outpath);
+        }
+
+        log::warn!("Entry collides by case with an earlier one: {}", outpath.display());
+        let file_name = outpath.file_name()?.to_string_lossy().into_owned();
+        let resolved = match policy {
+            CollisionPolicy::Overwrite => Some(outpath),
+            CollisionPolicy::Skip => None,
+            CollisionPolicy::Rename => {
+                let renamed = unique_output_path(outpath.parent()?, &file_name);
+                self.seen.insert(renamed.to_string_lossy().to_lowercase());
+                Some(renamed)
+            }
+        };
+        let status = match &resolved {
+            Some(path) if policy == CollisionPolicy::Rename => format!(
+                "Conflit de casse : {} renommé en {}",
+                file_name,
+                path.file_name().and_then(|n| n.to_str()).unwrap_or("...")
+            ),
+            Some(_) => format!("Conflit de casse : {} écrasé", file_name),
+            None => {
+                if let Err(e) = emit_file_skipped(app_handle, file_name.clone(), "Conflit de casse avec une entrée précédente") {
+                    log::warn!("Could not send skip event: {}", e);
+                }
+                format!("Conflit de casse : {} ignoré", file_name)
+            }
+        };
+        if let Err(e) = emit_status(app_handle, status) {
+            log::warn!("Could not send status event: {}", e);
+        }
+        resolved
+    }
+}
+
+// Entries an extraction left out instead of failing, for the end-of-run report
+#[derive(Default)]
+struct SkippedEntries {
+    skip_unwritable: bool,
+    skip_errors: bool,
+    // Targets that couldn't be created
+    unwritable: Vec<String>,
+    // Entries whose data couldn't be read back (corrupt, CRC mismatch)
+    failed: Vec<String>,
+    // Extracted, but preserve_ownership couldn't give them their stored owner
+    not_chowned: Vec<String>,
+    modified_after: Option<u64>,
+    // Files left out by modified_after
+    older: usize,
+    // Files this extraction created, the only ones extract_recursive looks into
+    written: Vec<std::path::PathBuf>,
+    // Bytes and files already written by the enclosing extractions of a nested archive,
+    // every level draws from the same zip bomb limits
+    spent_size: u64,
+    spent_files: usize,
+}
+
+impl SkippedEntries {
+    fn new(options: &DecryptOptions) -> Self {
+        SkippedEntries {
+            skip_unwritable: options.skip_unwritable,
+            skip_errors: options.skip_errors,
+            modified_after: options.modified_after,
+            ..Default::default()
+        }
+    }
+
+    // Whether modified_after leaves this file out, counted when it does
+    fn is_older(&mut self, mtime: Option<std::time::SystemTime>) -> bool {
+        let older = matches!(
+            (self.modified_after, mtime.and_then(unix_secs)),
+            (Some(after), Some(secs)) if secs < after
+        );
+        if older {
+            self.older += 1;
+        }
+        older
+    }
+
+    // What's left of MAX_TOTAL_SIZE and MAX_FILE_COUNT for this extraction
+    fn size_limit(&self) -> u64 {
+        MAX_TOTAL_SIZE.saturating_sub(self.spent_size)
+    }
+
+    fn file_limit(&self) -> usize {
+        MAX_FILE_COUNT.saturating_sub(self.spent_files)
+    }
+
+    // Creates the extraction target; None when it isn't writable and skipping is on
+    fn create<R: tauri::Runtime>(&mut self, app_handle: &tauri::AppHandle<R>, path: &Path) -> Result<Option<File>, String> {
+        match File::create(path) {
+            Ok(file) => {
+                self.written.push(path.to_path_buf());
+                Ok(Some(file))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+                if !self.skip_unwritable {
+                    return Err(format!("Permission denied: {}", path.display()));
+                }
+                log::warn!("Skipping unwritable target: {}", path.display());
+                emit_file_skipped(app_handle, path.display().to_string(), "Accès refusé").map_err(|e| e.to_string())?;
+                self.unwritable.push(path.display().to_string());
+                Ok(None)
+            }
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+// Same guards as the zip branch of decrypt_file, applied entry by entry
+fn extract_7z_guarded<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    cancel_flag: &AtomicBool,
+    path: &Path,
+    output_dir: &Path,
+    password: &str,
+    options: &DecryptOptions,
+    skipped: &mut SkippedEntries,
+) -> Result<(), String> {
+    let mut reader = sevenz_rust2::ArchiveReader::open(path, password.into())
+        .map_err(|e| e.to_string())?;
+
+    // Zip Bomb Protection (declared sizes, the actual bytes are checked while writing)
+    let files = &reader.archive().files;
+    if files.len() > skipped.file_limit() {
+        return Err(format!("Too many files in archive (limit: {})", MAX_FILE_COUNT));
+    }
+    let declared_size = files.iter().fold(0u64, |acc, f| acc.saturating_add(f.size()));
+    if declared_size > skipped.size_limit() {
+        return Err(format!("Total extracted size exceeds limit (limit: {} bytes)", MAX_TOTAL_SIZE));
+    }
+
+    let canonical_output_dir = output_dir.canonicalize().map_err(|e| e.to_string())?;
+    let mut progress = ProgressTracker::new(app_handle, declared_size);
+    let mut guard_error: Option<String> = None;
+    let mut collisions = CaseCollisions::new(output_dir);
+    let mut dir_times = Vec::new();
+
+    let res = reader.for_each_entries(|entry, entry_reader| {
+        let mut extract = || -> Result<(), String> {
+            if entry.is_anti_item() {
+                return Ok(());
+            }
+
+            // Zip Slip Protection
+            let outpath = safe_entry_output_path(output_dir, entry.name())?;
+            let outpath = map_output_path(output_dir, outpath, &options.path_map)?;
+
+            if entry.is_directory() {
+                fs::create_dir_all(&outpath).map_err(|e| e.to_string())?;
+                if entry.has_last_modified_date {
+                    dir_times.push((outpath, entry.last_modified_date().into()));
+                }
+                return Ok(());
+            }
+
+            if let Some(p) = outpath.parent() {
+                if !p.exists() {
+                    fs::create_dir_all(p).map_err(|e| e.to_string())?;
+                }
+                let canonical_parent = p.canonicalize().map_err(|e| e.to_string())?;
+                if !canonical_parent.starts_with(&canonical_output_dir) {
+                    return Err("Invalid file path (Zip Slip attempt detected)".to_string());
+                }
+            }
+
+            let mtime = entry.has_last_modified_date.then(|| entry.last_modified_date().into());
+            let outpath = match collisions.resolve(app_handle, outpath, options.case_collision_policy) {
+                Some(outpath) if !skipped.is_older(mtime) => outpath,
+                _ => {
+                    // Solid blocks have to be decoded in order, skip by draining
+                    copy_with_progress(entry_reader, &mut std::io::sink(), cancel_flag, &mut progress, "Decryption cancelled by user.")?;
+                    return Ok(());
+                }
+            };
+
+            let Some(mut outfile) = skipped.create(app_handle, &outpath)? else {
+                copy_with_progress(entry_reader, &mut std::io::sink(), cancel_flag, &mut progress, "Decryption cancelled by user.")?;
+                return Ok(());
+            };
+            let remaining = skipped.size_limit().saturating_sub(progress.done);
+            let copied = copy_with_progress(
+                &mut (&mut *entry_reader).take(remaining.saturating_add(1)),
+                &mut outfile,
+                cancel_flag,
+                &mut progress,
+                "Decryption cancelled by user.",
+            );
+            let written = match copied {
+                Ok(written) => written,
+                Err(e) if skipped.skip_errors && !cancel_flag.load(Ordering::SeqCst) => {
+                    log::warn!("Skipping damaged entry {}: {}", entry.name(), e);
+                    emit_file_skipped(app_handle, entry.name(), format!("Entrée endommagée : {}", e)).map_err(|e| e.to_string())?;
+                    drop(outfile);
+                    let _ = fs::remove_file(&outpath);
+                    // Keeps the next entry of a solid block aligned; a broken stream fails it too
+                    let _ = std::io::copy(entry_reader, &mut std::io::sink());
+                    skipped.failed.push(entry.name().to_string());
+                    return Ok(());
+                }
+                Err(e) => return Err(e),
+            };
+            if written > remaining {
+                return Err(format!("Total extracted size exceeds limit (limit: {} bytes)", MAX_TOTAL_SIZE));
+            }
+            if entry.has_last_modified_date {
+                if let Err(e) = outfile.set_modified(entry.last_modified_date().into()) {
+                    log::warn!("Could not restore timestamp of {}: {}", outpath.display(), e);
+                }
+            }
+            Ok(())
+        };
+
+        match extract() {
+            Ok(()) => Ok(true),
+            Err(e) => {
+                guard_error = Some(e);
+                // Returning an error is the only way to stop the remaining blocks too
+                Err(sevenz_rust2::Error::Other("Extraction aborted".into()))
+            }
+        }
+    });
+
+    if let Some(e) = guard_error {
+        return Err(e);
+    }
+    res.map_err(|e| e.to_string())?;
+    restore_dir_times(dir_times);
+    Ok(())
+}
+
+// RAR is extraction-only: unrar writes each entry itself, so the guards check
+// the header of every entry before handing it a destination path
+fn extract_rar_guarded<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    cancel_flag: &AtomicBool,
+    path: &Path,
+    output_dir: &Path,
+    password: &str,
+    options: &DecryptOptions,
+    skipped: &mut SkippedEntries,
+) -> Result<(), String> {
+    // Zip Bomb Protection, from the listing before anything is written
+    let mut total_size: u64 = 0;
+    let mut file_count: usize = 0;
+    let listing = unrar::Archive::with_password(path, password)
+        .open_for_listing()
+        .map_err(rar_error)?;
+    for header in listing {
+        let header = header.map_err(rar_error)?;
+        file_count += 1;
+        total_size = total_size.saturating_add(header.unpacked_size);
+    }
+    if file_count > skipped.file_limit() {
+        return Err(format!("Too many files in archive (limit: {})", MAX_FILE_COUNT));
+    }
+    if total_size > skipped.size_limit() {
+        return Err(format!("Total extracted size exceeds limit (limit: {} bytes)", MAX_TOTAL_SIZE));
+    }
+
+    let canonical_output_dir = output_dir.canonicalize().map_err(|e| e.to_string())?;
+    let mut total_extracted_size: u64 = 0;
+    let mut collisions = CaseCollisions::new(output_dir);
+    let mut dir_times = Vec::new();
+    let mut archive = unrar::Archive::with_password(path, password)
+        .open_for_processing()
+        .map_err(rar_error)?;
+
+    while let Some(header) = archive.read_header().map_err(rar_error)? {
+        if cancel_flag.load(Ordering::SeqCst) {
+            return Err("Decryption cancelled by user.".to_string());
+        }
+
+        let entry = header.entry();
+        let entry_name = entry.filename.to_string_lossy().to_string();
+        let entry_size = entry.unpacked_size;
+
+        // Zip Slip Protection
+        let outpath = safe_entry_output_path(output_dir, &entry_name)?;
+        let outpath = map_output_path(output_dir, outpath, &options.path_map)?;
+        // RAR headers carry a DOS timestamp, date in the high half
+        let dos_time = zip::DateTime::try_from_msdos((entry.file_time >> 16) as u16, entry.file_time as u16);
+        let mtime = dos_time.ok().and_then(system_time_from_zip_datetime);
+
+        archive = if entry.is_directory() {
+            fs::create_dir_all(&outpath).map_err(|e| e.to_string())?;
+            if let Some(mtime) = mtime {
+                dir_times.push((outpath, mtime));
+            }
+            header.skip().map_err(rar_error)?
+        } else {
+            if let Some(p) = outpath.parent() {
+                if !p.exists() {
+                    fs::create_dir_all(p).map_err(|e| e.to_string())?;
+                }
+                let canonical_parent = p.canonicalize().map_err(|e| e.to_string())?;
+                if !canonical_parent.starts_with(&canonical_output_dir) {
+                    return Err("Invalid file path (Zip Slip attempt detected)".to_string());
+                }
+            }
+            // The target is created first only to find out whether it can be written
+            match collisions.resolve(app_handle, outpath, options.case_collision_policy) {
+                Some(outpath) if !skipped.is_older(mtime) && skipped.create(app_handle, &outpath)?.is_some() => {
+                    header.extract_to(&outpath).map_err(rar_error)?
+                }
+                _ => header.skip().map_err(rar_error)?,
+            }
+        };
+
+        total_extracted_size += entry_size;
+        let progress = if total_size > 0 {
+            (total_extracted_size as f64 / total_size as f64 * 100.0) as u8
+        } else {
+            0
+        };
+        emit_progress(app_handle, progress).map_err(|e| e.to_string())?;
+        emit_progress_bytes(app_handle, total_extracted_size.min(total_size), total_size).map_err(|e| e.to_string())?;
+    }
+
+    restore_dir_times(dir_times);
+    Ok(())
+}
+
+fn zip_datetime_from_unix(secs: u64) -> zip::DateTime {
+    // DOS timestamps can't go before 1980, earlier values fall back to that
+    chrono::DateTime::from_timestamp(secs as i64, 0)
+        .and_then(|dt| {
+            zip::DateTime::from_date_and_time(
+                dt.year() as u16,
+                dt.month() as u8,
+                dt.day() as u8,
+                dt.hour() as u8,
+                dt.minute() as u8,
+                dt.second() as u8,
+            )
+            .ok()
+        })
+        .unwrap_or_default()
+}
+
+// FILETIME counts 100 ns ticks from 1601-01-01
+const NTFS_EPOCH_OFFSET_SECS: u64 = 11_644_473_600;
+
+fn ntfs_ticks(time: std::time::SystemTime) -> Option<u64> {
+    let since_epoch = time.duration_since(std::time::UNIX_EPOCH).ok()?;
+    let ticks = (since_epoch.as_nanos() / 100) as u64;
+    ticks.checked_add(NTFS_EPOCH_OFFSET_SECS * 10_000_000)
+}
+
+fn system_time_from_ntfs_ticks(ticks: u64) -> Option<std::time::SystemTime> {
+    let unix_ticks = ticks.checked_sub(NTFS_EPOCH_OFFSET_SECS * 10_000_000)?;
+    Some(std::time::UNIX_EPOCH + Duration::from_nanos(unix_ticks.saturating_mul(100)))
+}
+
+// The DOS time only has 2 s resolution and no zone, so every entry also gets the
+// NTFS field (100 ns mtime/atime/ctime) and the Unix extended timestamp (UTC seconds)
+fn with_entry_timestamps<'k>(mut entry_options: FullFileOptions<'k>, metadata: &fs::Metadata) -> FullFileOptions<'k> {
+    let Ok(modified) = metadata.modified() else {
+        return entry_options;
+    };
+    let accessed = metadata.accessed().unwrap_or(modified);
+    // Creation time isn't available on every filesystem
+    let created = metadata.created().unwrap_or(modified);
+
+    if let Ok(secs) = modified.duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs()) {
+        entry_options = entry_options.last_modified_time(zip_datetime_from_unix(secs));
+        if let Ok(secs) = u32::try_from(secs) {
+            let mut ut = vec![0x01];
+            ut.extend_from_slice(&secs.to_le_bytes());
+            let _ = entry_options.add_extra_data(0x5455, ut.into_boxed_slice(), false);
+        }
+    }
+
+    if let (Some(m), Some(a), Some(c)) = (ntfs_ticks(modified), ntfs_ticks(accessed), ntfs_ticks(created)) {
+        let mut ntfs = vec![0u8; 4]; // reserved
+        ntfs.extend_from_slice(&1u16.to_le_bytes()); // attribute tag 1: file times
+        ntfs.extend_from_slice(&24u16.to_le_bytes());
+        for ticks in [m, a, c] {
+            ntfs.extend_from_slice(&ticks.to_le_bytes());
+        }
+        let _ = entry_options.add_extra_data(0x000a, ntfs.into_boxed_slice(), false);
+    }
+    entry_options
+}
+
+// Info-ZIP "ux" field (0x7875): version 1, then uid and gid each with their byte size
+#[cfg(unix)]
+fn with_entry_owner<'k>(mut entry_options: FullFileOptions<'k>, metadata: &fs::Metadata) -> FullFileOptions<'k> {
+    use std::os::unix::fs::MetadataExt;
+    let mut ux = vec![1, 4];
+    ux.extend_from_slice(&metadata.uid().to_le_bytes());
+    ux.push(4);
+    ux.extend_from_slice(&metadata.gid().to_le_bytes());
+    let _ = entry_options.add_extra_data(0x7875, ux.into_boxed_slice(), false);
+    entry_options
+}
+
+#[cfg(not(unix))]
+fn with_entry_owner<'k>(entry_options: FullFileOptions<'k>, _metadata: &fs::Metadata) -> FullFileOptions<'k> {
+    entry_options
+}
+
+// Raw data of the first extra field with this header id
+fn zip_extra_field<'a>(file: &'a zip::read::ZipFile<'_>, wanted_id: u16) -> Option<&'a [u8]> {
+    let mut extra = file.extra_data()?;
+    while extra.len() >= 4 {
+        let header_id = u16::from_le_bytes([extra[0], extra[1]]);
+        let len = u16::from_le_bytes([extra[2], extra[3]]) as usize;
+        let data = extra.get(4..4 + len)?;
+        if header_id == wanted_id {
+            return Some(data);
+        }
+        extra = &extra[4 + len..];
+    }
+    None
+}
+
+fn zip_entry_owner(file: &zip::read::ZipFile<'_>) -> Option<(u32, u32)> {
+    // Little-endian id of up to 4 significant bytes
+    let id = |bytes: &[u8]| -> Option<u32> {
+        bytes.iter().rev().try_fold(0u32, |acc, &b| acc.checked_mul(256)?.checked_add(b as u32))
+    };
+    let data = zip_extra_field(file, 0x7875).filter(|data| data.first() == Some(&1))?;
+    let uid_size = *data.get(1)? as usize;
+    let uid = id(data.get(2..2 + uid_size)?)?;
+    let gid_size = *data.get(2 + uid_size)? as usize;
+    let gid = id(data.get(3 + uid_size..3 + uid_size + gid_size)?)?;
+    Some((uid, gid))
+}
+
+// Only root may hand files to another owner. Failures are only collected: without root
+// every file fails, the summary reports them once instead of a warning per file.
+#[cfg(unix)]
+fn restore_owner(path: &Path, owner: Option<(u32, u32)>, skipped: &mut SkippedEntries) {
+    if let Some((uid, gid)) = owner {
+        if std::os::unix::fs::chown(path, Some(uid), Some(gid)).is_err() {
+            skipped.not_chowned.push(path.display().to_string());
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn restore_owner(_path: &Path, _owner: Option<(u32, u32)>, _skipped: &mut SkippedEntries) {}
+
+// Most precise stored mtime: NTFS field, then Unix extended timestamp, then DOS time
+fn zip_entry_mtime(file: &zip::read::ZipFile<'_>) -> Option<std::time::SystemTime> {
+    let mut extended = None;
+    for field in file.extra_data_fields() {
+        match field {
+            zip::ExtraField::Ntfs(ntfs) => {
+                if let Some(time) = system_time_from_ntfs_ticks(ntfs.mtime()) {
+                    return Some(time);
+                }
+            }
+            zip::ExtraField::ExtendedTimestamp(ts) => {
+                extended = ts.mod_time().map(|secs| std::time::UNIX_EPOCH + Duration::from_secs(secs as u64));
+            }
+        }
+    }
+    extended.or_else(|| file.last_modified().and_then(system_time_from_zip_datetime))
+}
+
+fn system_time_from_zip_datetime(dt: zip::DateTime) -> Option<std::time::SystemTime> {
+    let secs = chrono::NaiveDate::from_ymd_opt(dt.year() as i32, dt.month() as u32, dt.day() as u32)?
+        .and_hms_opt(dt.hour() as u32, dt.minute() as u32, dt.second() as u32)?
+        .and_utc()
+        .timestamp();
+    Some(std::time::UNIX_EPOCH + Duration::from_secs(u64::try_from(secs).ok()?))
+}
+
+// Writing a file inside a directory bumps its mtime, so directory timestamps
+// are collected during extraction and only applied once everything is written
+fn restore_dir_times(mut dirs: Vec<(std::path::PathBuf, std::time::SystemTime)>) {
+    // Deepest first, a parent is never touched again after its own timestamp is set
+    dirs.sort_by(|a, b| b.0.cmp(&a.0));
+    for (dir, mtime) in dirs {
+        if let Err(e) = open_dir_for_times(&dir).and_then(|f| f.set_modified(mtime)) {
+            log::warn!("Could not restore timestamp of {}: {}", dir.display(), e);
+        }
+    }
+}
+
+#[cfg(windows)]
+fn open_dir_for_times(dir: &Path) -> std::io::Result<File> {
+    use std::os::windows::fs::OpenOptionsExt;
+    // Windows only opens a directory handle with backup semantics, and setting
+    // times needs write access on it
+    const FILE_FLAG_BACKUP_SEMANTICS: u32 = 0x0200_0000;
+    fs::OpenOptions::new()
+        .write(true)
+        .custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+        .open(dir)
+}
+
+#[cfg(not(windows))]
+fn open_dir_for_times(dir: &Path) -> std::io::Result<File> {
+    File::open(dir)
+}
+
+// Path ordering compares component-wise, so a directory always sorts right
+// before its own contents and the archive structure stays valid
+fn sort_entries(entries: &mut [CollectedEntry]) {
+    entries.sort_by(|a, b| a.rel_path.cmp(&b.rel_path));
+}
+
+// Some extractors create a folder twice when it has its own entry and appears in file paths
+fn drop_directory_entries(entries: &mut Vec<CollectedEntry>, keep_empty: bool) {
+    let parents: std::collections::HashSet<std::path::PathBuf> = entries
+        .iter()
+        .filter_map(|e| e.rel_path.parent().map(Path::to_path_buf))
+        .collect();
+    entries.retain(|e| !e.is_dir || (keep_empty && !parents.contains(&e.rel_path)));
+}
+
+// Leaves room for deflate's framing overhead on incompressible data
+const ZIP64_SIZE_THRESHOLD: u64 = u32::MAX as u64 - 1024 * 1024;
+const ZIP_MAX_ENTRIES: usize = u16::MAX as usize;
+
+fn needs_zip64(force_zip64: Option<bool>, size: u64) -> bool {
+    force_zip64.unwrap_or(size > ZIP64_SIZE_THRESHOLD)
+}
+
+struct CollectedEntry {
+    abs_path: std::path::PathBuf,
+    rel_path: std::path::PathBuf,
+    is_dir: bool,
+    size: u64,
+    // Set for dangling symlinks kept as links (BrokenSymlinkPolicy::StoreAsLink)
+    link_target: Option<std::path::PathBuf>,
+}
+
+const DEFAULT_RETRY_BACKOFF: Duration = Duration::from_millis(250);
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(8);
+
+// Network drives sometimes fail a call that works a moment later. Missing files
+// and permission errors are not retried, they won't fix themselves.
+fn is_transient_io_error(e: &std::io::Error) -> bool {
+    use std::io::ErrorKind;
+    matches!(
+        e.kind(),
+        ErrorKind::TimedOut
+            | ErrorKind::Interrupted
+            | ErrorKind::WouldBlock
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+    )
+}
+
+struct IoRetry {
+    attempts: usize,
+    backoff: Duration,
+    retries: AtomicUsize,
+}
+
+impl IoRetry {
+    fn new(options: &EncryptOptions) -> Self {
+        IoRetry {
+            attempts: options.retry_count,
+            backoff: options.retry_backoff_ms.map(Duration::from_millis).unwrap_or(DEFAULT_RETRY_BACKOFF),
+            retries: AtomicUsize::new(0),
+        }
+    }
+
+    fn run<T>(&self, mut op: impl FnMut() -> std::io::Result<T>) -> std::io::Result<T> {
+        let mut delay = self.backoff;
+        let mut attempt = 0;
+        loop {
+            match op() {
+                Err(e) if attempt < self.attempts && is_transient_io_error(&e) => {
+                    attempt += 1;
+                    self.retries.fetch_add(1, Ordering::SeqCst);
+                    log::warn!("Transient I/O error, retry {}/{} in {:?}: {}", attempt, self.attempts, delay, e);
+                    std::thread::sleep(delay);
+                    delay = (delay * 2).min(MAX_RETRY_BACKOFF);
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+// Writes one collected entry, `on_bytes` is called after every chunk of file data.
+// Returns the bytes actually read, a live file may have changed since collection.
+fn write_zip_entry<W: Write + Seek>(
+    zip: &mut ZipWriter<W>,
+    entry: &CollectedEntry,
+    file_options: FullFileOptions<'_>,
+    options: &EncryptOptions,
+    cancel_flag: &AtomicBool,
+    retry: &IoRetry,
+    mut on_bytes: impl FnMut(u64) -> Result<(), String>,
+) -> Result<u64, String> {
+    let rel_str = entry.rel_path.to_str().ok_or("Invalid path encoding")?;
+    let mut written: u64 = 0;
+
+    if let Some(target) = &entry.link_target {
+        let target_str = target.to_str().ok_or("Invalid path encoding")?;
+        zip.add_symlink(rel_str, target_str, file_options)
+            .map_err(|e| format!("Failed to add symlink: {}", e))?;
+    } else if entry.is_dir {
+        zip.add_directory(rel_str, file_options)
+            .map_err(|e| format!("Failed to add directory: {}", e))?;
+    } else {
+        let mut eol = options.text_mode.as_ref().and_then(|t| t.eol_for(&entry.rel_path)).map(EolConverter::new);
+        // CRLF output can be up to twice the size of the file
+        let max_size = match eol {
+            Some(EolConverter { eol: LineEnding::Crlf, .. }) => entry.size.saturating_mul(2),
+            _ => entry.size,
+        };
+        zip.start_file(rel_str, file_options.large_file(needs_zip64(options.force_zip64, max_size)))
+            .map_err(|e| format!("Failed to start file in zip: {}", e))?;
+
+        let mut f = retry
+            .run(|| File::open(&entry.abs_path))
+            .map_err(|e| format!("Failed to open file: {}", e))?;
+
+        let mut buffer = vec![0; COPY_BUFFER_SIZE];
+        let mut converted = Vec::new();
+        loop {
+            if cancel_flag.load(Ordering::SeqCst) {
+                return Err("Encryption cancelled by user.".to_string());
+            }
+            let bytes_read = retry
+                .run(|| f.read(&mut buffer))
+                .map_err(|e| format!("Failed to read file: {}", e))?;
+            if bytes_read == 0 {
+                if let Some(eol) = &mut eol {
+                    eol.finish(&mut converted);
+                    zip.write_all(&converted)
+                        .map_err(|e| format!("Failed to write to zip: {}", e))?;
+                }
+                break;
+            }
+            let chunk = match &mut eol {
+                Some(eol) => {
+                    eol.convert(&buffer[..bytes_read], &mut converted);
+                    &converted
+                }
+                None => &buffer[..bytes_read],
+            };
+            zip.write_all(chunk)
+                .map_err(|e| format!("Failed to write to zip: {}", e))?;
+            // Counts what was read, so converted text files aren't reported as changed
+            written += bytes_read as u64;
+            on_bytes(bytes_read as u64)?;
+        }
+    }
+
+    Ok(written)
+}
+
+// Reading stops at EOF rather than at the collected size, so a file that grew is
+// stored whole; this only flags it for the end-of-run warning
+fn size_changed(entry: &CollectedEntry, written: u64) -> bool {
+    !entry.is_dir && entry.link_target.is_none() && written != entry.size
+}
+
+// Each worker compresses whole entries into single-entry temp zips; the calling thread
+// merges them back in collection order, so the archive matches the sequential one
+fn write_zip_parallel<'k, W: Write + Seek, R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    cancel_flag: &AtomicBool,
+    zip: &mut ZipWriter<W>,
+    entries: &[CollectedEntry],
+    options: &EncryptOptions,
+    retry: &IoRetry,
+    entry_options: &(dyn Fn(&CollectedEntry) -> FullFileOptions<'k> + Sync),
+) -> Result<Vec<String>, String> {
+    let total_size = entries.iter().fold(0u64, |acc, e| acc.saturating_add(e.size));
+    let next_index = AtomicUsize::new(0);
+    let processed = AtomicU64::new(0);
+    // Stops the workers on cancellation or on the first error
+    let stop = AtomicBool::new(false);
+
+    std::thread::scope(|scope| {
+        let (tx, rx) = std::sync::mpsc::channel();
+        for _ in 0..options.parallel_workers.min(entries.len()) {
+            let tx = tx.clone();
+            let (next_index, processed, stop) = (&next_index, &processed, &stop);
+            scope.spawn(move || loop {
+                let index = next_index.fetch_add(1, Ordering::SeqCst);
+                if index >= entries.len() || stop.load(Ordering::SeqCst) {
+                    break;
+                }
+                let entry = &entries[index];
+                let result = app_temp_file(".zip")
+                    .map_err(|e| e.to_string())
+                    .and_then(|temp| {
+                        let mut part = ZipWriter::new(temp);
+                        let written = write_zip_entry(&mut part, entry, entry_options(entry), options, stop, retry, |bytes| {
+                            processed.fetch_add(bytes, Ordering::SeqCst);
+                            Ok(())
+                        })?;
+                        let part = part.finish().map_err(|e| format!("Failed to finish zip: {}", e))?;
+                        Ok((part, written))
+                    });
+                let failed = result.is_err();
+                if tx.send((index, result)).is_err() || failed {
+                    break;
+                }
+            });
+        }
+        drop(tx);
+
+        let result = (|| -> Result<Vec<String>, String> {
+            let mut progress = ProgressTracker::bytes_or_entries(app_handle, total_size, entries.len());
+            let mut finished = std::collections::BTreeMap::new();
+            let mut next_to_merge = 0;
+            let mut changed = Vec::new();
+
+            while next_to_merge < entries.len() {
+                if cancel_flag.load(Ordering::SeqCst) {
+                    return Err("Encryption cancelled by user.".to_string());
+                }
+                match rx.recv_timeout(Duration::from_millis(100)) {
+                    Ok((index, part)) => {
+                        finished.insert(index, part?);
+                    }
+                    Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
+                    Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
+                        return Err("Compression workers stopped unexpectedly".to_string());
+                    }
+                }
+
+                while let Some((part, written)) = finished.remove(&next_to_merge) {
+                    let entry = &entries[next_to_merge];
+                    if size_changed(entry, written) {
+                        changed.push(entry.abs_path.display().to_string());
+                    }
+                    let part = zip::ZipArchive::new(part).map_err(|e| e.to_string())?;
+                    zip.merge_archive(part)
+                        .map_err(|e| format!("Failed to write to zip: {}", e))?;
+                    next_to_merge += 1;
+                    progress.finish_entry();
+                }
+                progress.advance(processed.load(Ordering::SeqCst) - progress.done);
+            }
+            Ok(changed)
+        })();
+
+        if result.is_err() {
+            stop.store(true, Ordering::SeqCst);
+        }
+        result
+    })
+}
+
+// Extended attributes are never stored (neither writer reads them), only the
+// Finder's on-disk leftovers need filtering
+fn is_mac_metadata(name: &std::ffi::OsStr) -> bool {
+    let name = name.to_string_lossy();
+    name == "__MACOSX" || name == ".DS_Store" || name.starts_with("._")
+}
+
+fn collect_entries<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    file_paths: &[String],
+    canonical_output_path: &Path,
+    options: &EncryptOptions,
+    retry: &IoRetry,
+) -> Result<(Vec<CollectedEntry>, u64, usize, SkippedBySize), String> {
+    let mut entries = Vec::new();
+    let mut total_size = 0u64;
+    let mut skipped_by_depth = 0;
+    let mut skipped_by_size = SkippedBySize::default();
+    let strip_mac_metadata = options.strip_mac_metadata.unwrap_or(true);
+
+    // Below the selected paths the archive being written is skipped quietly, but picking
+    // it directly is almost certainly a mistake and would give an empty archive
+    let output_selected = file_paths
+        .iter()
+        .find(|root| Path::new(root).canonicalize().is_ok_and(|p| p == canonical_output_path));
+    if let Some(root) = output_selected {
+        return Err(format!("{} is both an input and the output archive", root));
+    }
+
+    let base_dir = options.base_dir.as_deref().map(Path::new);
+    if let Some(base_dir) = base_dir {
+        // Checked for every root before walking any, a bad selection fails fast
+        let outside = file_paths
+            .iter()
+            .find(|root| Path::new(root) == base_dir || !Path::new(root).starts_with(base_dir));
+        if let Some(root) = outside {
+            return Err(format!("{} is not inside the base directory {}", root, base_dir.display()));
+        }
+    }
+
+    for file_path_str in file_paths {
+        let root = Path::new(file_path_str);
+        let parent = base_dir.unwrap_or_else(|| root.parent().unwrap_or(Path::new("/")));
+
+        // Explicitly selected paths are always kept, the filter only applies below them
+        // One level further than asked, only to count what the limit leaves out
+        let mut walker = WalkDir::new(root).same_file_system(options.one_file_system);
+        if let Some(max_depth) = options.max_depth {
+            walker = walker.max_depth(max_depth.saturating_add(1));
+        }
+        let walker = walker.into_iter().filter_entry(|e| {
+            !strip_mac_metadata || e.depth() == 0 || !is_mac_metadata(e.file_name())
+        });
+
+        for entry in walker {
+            let entry = entry.map_err(|e| e.to_string())?;
+            let entry_path = entry.path();
+
+            if options.max_depth.is_some_and(|max_depth| entry.depth() > max_depth) {
+                skipped_by_depth += 1;
+                continue;
+            }
+
+            // Optimization: Only check full path if file name matches output file name
+            // This prevents the infinite recursion loop without checking every single file
+            if let Some(name) = canonical_output_path.file_name() {
+                if entry.file_name() == name {
+                    if let Ok(p) = entry_path.canonicalize() {
+                        if p == canonical_output_path {
+                            continue;
+                        }
+                    }
+                }
+            }
+
+            let rel = entry_path
+                .strip_prefix(parent)
+                .map_err(|e| e.to_string())?
+                .to_path_buf();
+
+            // A dangling symlink can't be opened, decide up front what to do with it
+            if entry.path_is_symlink() && fs::metadata(entry_path).is_err() {
+                match options.broken_symlink_policy {
+                    BrokenSymlinkPolicy::Skip => {
+                        log::warn!("Skipping broken symlink: {}", entry_path.display());
+                        emit_file_skipped(app_handle, entry_path.display().to_string(), "Lien symbolique cassé").map_err(|e| e.to_string())?;
+                        continue;
+                    }
+                    BrokenSymlinkPolicy::Error => {
+                        return Err(format!("Broken symlink: {}", entry_path.display()));
+                    }
+                    BrokenSymlinkPolicy::StoreAsLink => {
+                        let target = fs::read_link(entry_path).map_err(|e| e.to_string())?;
+                        entries.push(CollectedEntry {
+                            abs_path: entry_path.to_path_buf(),
+                            rel_path: rel,
+                            is_dir: false,
+                            size: 0,
+                            link_target: Some(target),
+                        });
+                        continue;
+                    }
+                }
+            }
+
+            // Links are followed when the entry is read, so it's the target's type that counts
+            let is_special = if entry.path_is_symlink() {
+                fs::metadata(entry_path).is_ok_and(|m| !m.is_file() && !m.is_dir())
+            } else {
+                !entry.file_type().is_file() && !entry.file_type().is_dir()
+            };
+            if is_special {
+                match options.special_files_policy {
+                    SpecialFilePolicy::Skip => {
+                        log::warn!("Skipping special file (pipe, socket or device): {}", entry_path.display());
+                        emit_file_skipped(app_handle, entry_path.display().to_string(), "Fichier spécial (tube, socket ou périphérique)").map_err(|e| e.to_string())?;
+                        continue;
+                    }
+                    SpecialFilePolicy::Error => {
+                        return Err(format!("Cannot archive special file (pipe, socket or device): {}", entry_path.display()));
+                    }
+                }
+            }
+
+            let is_dir = entry.file_type().is_dir();
+            let size = if is_dir {
+                0
+            } else {
+                retry
+                    .run(|| entry.metadata().map_err(std::io::Error::from))
+                    .map_err(|e| e.to_string())?
+                    .len()
+            };
+
+            if !is_dir {
+                let too_small = options.min_size.is_some_and(|min| size < min);
+                let too_large = options.max_size.is_some_and(|max| size > max);
+                if too_small || too_large {
+                    skipped_by_size.files += 1;
+                    skipped_by_size.bytes = skipped_by_size.bytes.saturating_add(size);
+                    continue;
+                }
+                total_size = total_size.saturating_add(size);
+            }
+
+            entries.push(CollectedEntry {
+                abs_path: entry_path.to_path_buf(),
+                rel_path: rel,
+                is_dir,
+                size,
+                link_target: None,
+            });
+        }
+    }
+
+    Ok((entries, total_size, skipped_by_depth, skipped_by_size))
+}
+
+// Renders the per-item archive name for separate_archives; the extension is added when missing
+fn render_filename_template(
+    template: &str,
+    source_path: &str,
+    index: usize,
+    now: &chrono::DateTime<chrono::Local>,
+    extension: &str,
+) -> Result<String, String> {
+    let source = Path::new(source_path);
+    let name = if source.is_dir() { source.file_name() } else { source.file_stem() }
+        .and_then(|n| n.to_str())
+        .ok_or("Invalid path encoding")?;
+
+    let template = if template.is_empty() { "{name}" } else { template };
+    let mut rendered = String::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        rendered.push_str(&rest[..open]);
+        let close = rest[open..]
+            .find('}')
+            .ok_or("Unclosed placeholder in filename template")?;
+        match &rest[open + 1..open + close] {
+            "name" => rendered.push_str(name),
+            "date" => rendered.push_str(&now.format("%Y-%m-%d").to_string()),
+            "time" => rendered.push_str(&now.format("%H-%M-%S").to_string()),
+            "index" => rendered.push_str(&index.to_string()),
+            other => return Err(format!("Unknown placeholder in filename template: {{{}}}", other)),
+        }
+        rest = &rest[open + close + 1..];
+    }
+    rendered.push_str(rest);
+
+    let rendered = rendered.trim().to_string();
+    let illegal = |c: char| c.is_control() || matches!(c, '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*');
+    if rendered.is_empty() || rendered == "." || rendered == ".." || rendered.contains(illegal) {
+        return Err(format!("Filename template produces an invalid file name: {:?}", rendered));
+    }
+
+    if rendered.to_lowercase().ends_with(extension) {
+        Ok(rendered)
+    } else {
+        Ok(format!("{}{}", rendered, extension))
+    }
+}
+
+// Appends " (2)", " (3)"... before the extension until the name is free
+fn unique_output_path(dir: &Path, file_name: &str) -> std::path::PathBuf {
+    let candidate = dir.join(file_name);
+    if !candidate.exists() {
+        return candidate;
+    }
+    let (stem, extension) = match file_name.rfind('.') {
+        Some(dot) if dot > 0 => file_name.split_at(dot),
+        _ => (file_name, ""),
+    };
+    (2..)
+        .map(|n| dir.join(format!("{} ({}){}", stem, n, extension)))
+        .find(|path| !path.exists())
+        .unwrap()
+}
+
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ManifestEntry {
+    size: u64,
+    // Unix milliseconds, seconds are too coarse to catch quick successive edits
+    mtime: u64,
+    crc32: u32,
+}
+
+// Relative entry path (always '/' separated) to what the file looked like when archived
+type Manifest = std::collections::BTreeMap<String, ManifestEntry>;
+
+// Entry holding the opaque -> real name mapping of an obfuscated archive
+const NAME_MANIFEST_ENTRY: &str = "manifest.json";
+// Random filler added by pad_zip, skipped on extraction
+const PADDING_ENTRY: &str = ".eazip-padding";
+// Copies left out by deduplicate, see DedupManifest
+const DEDUP_MANIFEST_ENTRY: &str = ".eazip-duplicates.json";
+// Labels the hint line so it isn't mistaken for any other comment
+const PASSWORD_HINT_LABEL: &str = "EaZip password hint: ";
+
+fn is_opaque_name(name: &str) -> bool {
+    let name = name.strip_suffix('/').unwrap_or(name);
+    !name.is_empty() && name.bytes().all(|b| b.is_ascii_digit())
+}
+
+// Renames every entry to its 1-based position and returns the mapping back
+fn obfuscate_entry_names(entries: &mut [CollectedEntry]) -> std::collections::BTreeMap<String, String> {
+    let mut names = std::collections::BTreeMap::new();
+    for (i, entry) in entries.iter_mut().enumerate() {
+        let opaque = format!("{:04}", i + 1);
+        names.insert(opaque.clone(), entry.rel_path.to_string_lossy().replace('\\', "/"));
+        entry.rel_path = std::path::PathBuf::from(opaque);
+    }
+    names
+}
+
+// Only archives laid out exactly as obfuscate_entry_names writes them are treated
+// as obfuscated, a regular zip that happens to contain a manifest.json is left alone
+fn read_name_manifest<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    path: &Path,
+    password: &str,
+) -> Result<Option<std::collections::BTreeMap<String, String>>, String> {
+    let Some(index) = archive.index_for_name(NAME_MANIFEST_ENTRY) else {
+        return Ok(None);
+    };
+    if !archive.file_names().all(|n| n == NAME_MANIFEST_ENTRY || n == PADDING_ENTRY || is_opaque_name(n)) {
+        return Ok(None);
+    }
+    let mut data = String::new();
+    archive
+        .by_index_decrypt(index, password.as_bytes())
+        .map_err(|e| zip_error_message(path, e))?
+        .read_to_string(&mut data)
+        .map_err(|e| e.to_string())?;
+    serde_json::from_str(&data)
+        .map(Some)
+        .map_err(|e| format!("Invalid name manifest: {}", e))
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+struct DedupReference {
+    // Entry holding the content
+    source: String,
+    // Unix seconds of the copy itself
+    mtime: Option<u64>,
+}
+
+// Path of each copy left out of the archive ('/' separated, like entry names) to the
+// entry it shares content with, stored as JSON in DEDUP_MANIFEST_ENTRY:
+//   { "photos/b.jpg": { "source": "photos/a.jpg", "mtime": 1700000000 } }
+type DedupManifest = std::collections::BTreeMap<String, DedupReference>;
+
+// Drops every file whose content matches an earlier entry and returns where to find it.
+// Links and empty files are always stored as they are.
+fn deduplicate_entries<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    cancel_flag: &AtomicBool,
+    entries: &mut Vec<CollectedEntry>,
+    fixed_mtime: Option<u64>,
+) -> Result<DedupManifest, String> {
+    let candidates: Vec<usize> = entries
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| !e.is_dir && e.link_target.is_none())
+        .map(|(i, _)| i)
+        .collect();
+    let files: Vec<_> = candidates.iter().map(|&i| (entries[i].abs_path.clone(), entries[i].size)).collect();
+    let groups = duplicate_groups(app_handle, cancel_flag, &files, "Encryption cancelled by user.")?;
+
+    let entry_name = |entry: &CollectedEntry| entry.rel_path.to_string_lossy().replace('\\', "/");
+    let mut manifest = DedupManifest::new();
+    let mut dropped = std::collections::HashSet::new();
+    for (_, group) in groups {
+        let source = entry_name(&entries[candidates[group[0]]]);
+        for &copy in &group[1..] {
+            let entry = &entries[candidates[copy]];
+            let mtime = fixed_mtime.or_else(|| fs::metadata(&entry.abs_path).and_then(|m| m.modified()).ok().and_then(unix_secs));
+            manifest.insert(entry_name(entry), DedupReference { source: source.clone(), mtime });
+            dropped.insert(candidates[copy]);
+        }
+    }
+    let mut index = 0;
+    entries.retain(|_| {
+        index += 1;
+        !dropped.contains(&(index - 1))
+    });
+    Ok(manifest)
+}
+
+// A zip that merely contains a file with this name but isn't a manifest is extracted as is
+fn read_dedup_manifest<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    path: &Path,
+    password: &str,
+) -> Result<Option<DedupManifest>, String> {
+    let Some(index) = archive.index_for_name(DEDUP_MANIFEST_ENTRY) else {
+        return Ok(None);
+    };
+    let mut data = String::new();
+    archive
+        .by_index_decrypt(index, password.as_bytes())
+        .map_err(|e| zip_error_message(path, e))?
+        .read_to_string(&mut data)
+        .map_err(|e| e.to_string())?;
+    match serde_json::from_str(&data) {
+        Ok(manifest) => Ok(Some(manifest)),
+        Err(e) => {
+            log::warn!("{} is not a duplicates manifest, extracting it as a file: {}", DEDUP_MANIFEST_ENTRY, e);
+            Ok(None)
+        }
+    }
+}
+
+// Recreates the copies from the files they share content with, once those are extracted.
+// Copies count against the same file and size limits as entries, so a small manifest
+// can't be used to blow up the output.
+fn restore_duplicates(
+    output_dir: &Path,
+    duplicates: &DedupManifest,
+    extracted: &std::collections::HashMap<String, std::path::PathBuf>,
+    options: &DecryptOptions,
+    skipped: &mut SkippedEntries,
+    extracted_count: &mut usize,
+    extracted_size: u64,
+) -> Result<(), String> {
+    let canonical_output_dir = output_dir.canonicalize().map_err(|e| e.to_string())?;
+    let mut total_size = extracted_size;
+    for (copy, reference) in duplicates {
+        if skipped.is_older(reference.mtime.map(|mtime| std::time::UNIX_EPOCH + Duration::from_secs(mtime))) {
+            continue;
+        }
+        let Some(source) = extracted.get(&reference.source) else {
+            log::warn!("Not restoring {}: {} wasn't extracted", copy, reference.source);
+            continue;
+        };
+        *extracted_count += 1;
+        if *extracted_count > skipped.file_limit() {
+            return Err(format!("Too many files in archive (limit: {})", MAX_FILE_COUNT));
+        }
+        total_size = total_size.saturating_add(fs::metadata(source).map_err(|e| e.to_string())?.len());
+        if total_size > skipped.size_limit() {
+            return Err(format!("Total extracted size exceeds limit (limit: {} bytes)", MAX_TOTAL_SIZE));
+        }
+
+        let outpath = safe_entry_output_path(output_dir, copy)?;
+        let outpath = map_output_path(output_dir, outpath, &options.path_map)?;
+        if let Some(parent) = outpath.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            if !parent.canonicalize().map_err(|e| e.to_string())?.starts_with(&canonical_output_dir) {
+                return Err("Invalid file path (Zip Slip attempt detected)".to_string());
+            }
+        }
+        fs::copy(source, &outpath).map_err(|e| format!("Failed to restore {}: {}", outpath.display(), e))?;
+        skipped.written.push(outpath.clone());
+        if let Some(mtime) = reference.mtime {
+            let restored = File::options()
+                .write(true)
+                .open(&outpath)
+                .and_then(|f| f.set_modified(std::time::UNIX_EPOCH + Duration::from_secs(mtime)));
+            if let Err(e) = restored {
+                log::warn!("Could not restore timestamp of {}: {}", outpath.display(), e);
+            }
+        }
+    }
+    Ok(())
+}
+
+const MIN_RANDOM_PADDING: u64 = 64 * 1024;
+
+// Adds a stored entry of random bytes (encrypted like the others) to a finished zip.
+// Unlike bytes after the end of central directory, which some readers reject, an extra
+// entry is valid everywhere; other tools just extract it as a junk file. Its overhead
+// doesn't depend on its length, so it's measured on an empty copy first and the
+// archive lands exactly on the target size.
+fn pad_zip(path: &Path, entry_options: FullFileOptions<'_>, options: &EncryptOptions) -> Result<(), String> {
+    let entry_options = entry_options.compression_method(CompressionMethod::Stored);
+    let overhead = {
+        let empty = ZipWriter::new(std::io::Cursor::new(Vec::new()))
+            .finish()
+            .map_err(|e| e.to_string())?
+            .into_inner()
+            .len();
+        let mut probe = ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        probe.start_file(PADDING_ENTRY, entry_options.clone()).map_err(|e| e.to_string())?;
+        (probe.finish().map_err(|e| e.to_string())?.into_inner().len() - empty) as u64
+    };
+
+    let size = fs::metadata(path).map_err(|e| e.to_string())?.len();
+    let mut rng = rand::thread_rng();
+    let mut target = size + overhead;
+    if options.random_padding {
+        target += rng.gen_range(0..=(size / 20).max(MIN_RANDOM_PADDING));
+    }
+    if let Some(block) = options.pad_to {
+        target = target.div_ceil(block) * block;
+    }
+
+    let file = fs::OpenOptions::new().read(true).write(true).open(path).map_err(|e| e.to_string())?;
+    let cd_start = zip::ZipArchive::new(&file).map_err(|e| e.to_string())?.central_directory_start();
+    let mut zip = ZipWriter::new_append(&file).map_err(|e| e.to_string())?;
+    // finish writes the whole central directory again, so the old one is cut off rather
+    // than left behind between the last entry and the padding
+    (&file).seek(SeekFrom::Start(cd_start)).map_err(|e| e.to_string())?;
+    file.set_len(cd_start).map_err(|e| e.to_string())?;
+    zip.start_file(PADDING_ENTRY, entry_options).map_err(|e| e.to_string())?;
+    let mut remaining = target - size - overhead;
+    let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+    while remaining > 0 {
+        let len = remaining.min(buffer.len() as u64) as usize;
+        rng.fill(&mut buffer[..len]);
+        zip.write_all(&buffer[..len]).map_err(|e| e.to_string())?;
+        remaining -= len as u64;
+    }
+    zip.finish().map_err(|e| e.to_string())?;
+    file.sync_all().map_err(|e| e.to_string())
+}
+
+fn manifest_key(rel_path: &Path) -> String {
+    rel_path.to_string_lossy().replace('\\', "/")
+}
+
+fn file_mtime_ms(metadata: &fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+fn build_manifest_entries(entries: &[CollectedEntry]) -> Result<Manifest, String> {
+    let mut manifest = Manifest::new();
+    for entry in entries.iter().filter(|e| !e.is_dir && e.link_target.is_none()) {
+        let metadata = fs::metadata(&entry.abs_path).map_err(|e| e.to_string())?;
+        let crc32 = file_crc32(&entry.abs_path).map_err(|e| e.to_string())?;
+        manifest.insert(
+            manifest_key(&entry.rel_path),
+            ManifestEntry { size: metadata.len(), mtime: file_mtime_ms(&metadata), crc32 },
+        );
+    }
+    Ok(manifest)
+}
+
+// Keeps the files that are new or changed since `previous`, and returns the manifest of
+// the whole current tree. Size and mtime decide first, the CRC only settles touched files.
+// Directories are dropped: extraction recreates parents, but empty folders are not carried.
+fn filter_changed_entries(
+    entries: Vec<CollectedEntry>,
+    previous: &Manifest,
+) -> Result<(Vec<CollectedEntry>, Manifest), String> {
+    let mut changed = Vec::new();
+    let mut manifest = Manifest::new();
+
+    for entry in entries {
+        if entry.is_dir {
+            continue;
+        }
+        let key = manifest_key(&entry.rel_path);
+        if entry.link_target.is_some() {
+            if !previous.contains_key(&key) {
+                changed.push(entry);
+            }
+            manifest.insert(key, ManifestEntry { size: 0, mtime: 0, crc32: 0 });
+            continue;
+        }
+
+        let metadata = fs::metadata(&entry.abs_path).map_err(|e| e.to_string())?;
+        let (size, mtime) = (metadata.len(), file_mtime_ms(&metadata));
+        let current = match previous.get(&key) {
+            Some(old) if old.size == size && old.mtime == mtime => old.clone(),
+            old => {
+                let crc32 = file_crc32(&entry.abs_path).map_err(|e| e.to_string())?;
+                if !old.is_some_and(|old| old.size == size && old.crc32 == crc32) {
+                    changed.push(entry);
+                }
+                ManifestEntry { size, mtime, crc32 }
+            }
+        };
+        manifest.insert(key, current);
+    }
+
+    Ok((changed, manifest))
+}
+
+// Baseline for incremental runs, collected with the same rules as encrypt_files
+#[tauri::command]
+async fn build_manifest(
+    app_handle: tauri::AppHandle,
+    file_paths: Vec<String>,
+    options: Option<EncryptOptions>,
+) -> Result<Manifest, String> {
+    let options = options.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || {
+        let (entries, ..) = collect_entries(&app_handle, &file_paths, Path::new(""), &options, &IoRetry::new(&options))?;
+        build_manifest_entries(&entries)
+    }).await.map_err(|e| e.to_string())?
+}
+
+fn encrypt_to_path<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    cancel_flag: &AtomicBool,
+    file_paths: &[String],
+    output_path: &str,
+    password: &str,
+    encryption_method: &EncryptionMethod,
+    options: &EncryptOptions,
+) -> Result<EncryptionSummary, String> {
+    emit_status(app_handle, "Analyse des fichiers...").map_err(|e| e.to_string())?;
+
+    // Canonicalize output path to prevent recursion
+    let canonical_output_path = Path::new(&output_path).canonicalize().unwrap_or_else(|_| Path::new(&output_path).to_path_buf());
+
+    let retry = IoRetry::new(options);
+
+    // Single pass collection
+    let (mut entries, mut total_size, skipped_by_depth, skipped_by_size) = collect_entries(app_handle, file_paths, &canonical_output_path, options, &retry)?;
+
+    let manifest = match &options.previous_manifest {
+        Some(previous) => {
+            let (changed, manifest) = filter_changed_entries(entries, previous)?;
+            entries = changed;
+            total_size = entries.iter().fold(0u64, |acc, e| acc.saturating_add(e.size));
+            Some(manifest)
+        }
+        None => None,
+    };
+
+    // A fixed mtime is only useful for reproducible archives if the order is stable too.
+    // Note that the encryption salts stay random, only the layout becomes deterministic.
+    if options.deterministic || options.fixed_mtime.is_some() {
+        sort_entries(&mut entries);
+    }
+
+    if !options.store_directory_entries.unwrap_or(true) {
+        drop_directory_entries(&mut entries, options.keep_empty_directories);
+    }
+
+    let duplicates = if options.deduplicate {
+        emit_status(app_handle, "Recherche des doublons...").map_err(|e| e.to_string())?;
+        let duplicates = deduplicate_entries(app_handle, cancel_flag, &mut entries, options.fixed_mtime)?;
+        total_size = entries.iter().filter(|e| !e.is_dir).fold(0u64, |acc, e| acc.saturating_add(e.size));
+        Some(duplicates)
+    } else {
+        None
+    };
+
+    let real_names = options.obfuscate_names.then(|| obfuscate_entry_names(&mut entries));
+    let compression = options.compression.as_deref().map(parse_compression_spec).transpose()?;
+
+    // Files whose size changed between collection and writing (live logs...)
+    let mut size_changes: Vec<String> = Vec::new();
+    let auto_stored = AtomicUsize::new(0);
+
+    let message: Result<String, String> = match encryption_method {
+        EncryptionMethod::SevenZip => {
+            // The staged copies are plaintext: the folder is the owner's alone (see
+            // app_temp_dir), and the copies drop the group/other bits fs::copy carries over
+            let temp_dir = app_temp_dir().map_err(|e| staging_error("Failed to create a temp folder".to_string(), e))?;
+            let temp_dir_path = extended_length_path(temp_dir.path());
+
+            emit_status(app_handle, "Préparation de la copie...").map_err(|e| e.to_string())?;
+            emit_progress(app_handle, 0).map_err(|e| e.to_string())?; // Stage 1: Setup
+
+            let mut bytes_copied: u64 = 0;
+            let mut last_update_time = Instant::now();
+            let mut last_progress_percent: u8 = 0;
+            // The file name only goes out again once a different entry is being copied
+            let mut last_status_entry: Option<&Path> = None;
+
+            for (index, entry) in entries.iter().enumerate() {
+                if cancel_flag.load(Ordering::SeqCst) {
+                    return Err("Encryption cancelled by user.".to_string());
+                }
+
+                let dest_path = temp_dir_path.join(&entry.rel_path);
+
+                if entry.link_target.is_some() {
+                    // The 7z writer reads every entry as a file, a dangling link can't be stored
+                    log::warn!("7z cannot store broken symlink, skipping: {}", entry.abs_path.display());
+                    emit_file_skipped(app_handle, entry.abs_path.display().to_string(), "Lien symbolique cassé, non pris en charge par 7z").map_err(|e| e.to_string())?;
+                    continue;
+                }
+
+                if entry.is_dir {
+                    fs::create_dir_all(&dest_path)
+                        .map_err(|e| staging_error(format!("Failed to create {}", dest_path.display()), e))?;
+                } else {
+                    if let Some(p) = dest_path.parent() {
+                        fs::create_dir_all(p).map_err(|e| staging_error(format!("Failed to create {}", p.display()), e))?;
+                    }
+                    let copied = retry.run(|| fs::copy(&entry.abs_path, &dest_path)).map_err(|e| {
+                        staging_error(format!("Failed to copy {} to {}", entry.abs_path.display(), dest_path.display()), e)
+                    })?;
+                    if size_changed(entry, copied) {
+                        size_changes.push(entry.abs_path.display().to_string());
+                    }
+                    #[cfg(unix)]
+                    {
+                        use std::os::unix::fs::PermissionsExt;
+                        fs::set_permissions(&dest_path, fs::Permissions::from_mode(0o600))
+                            .map_err(|e| staging_error(format!("Failed to restrict {}", dest_path.display()), e))?;
+                    }
+                    // The 7z writer takes timestamps from the staged copy, which fs::copy doesn't carry over
+                    let mtime = match options.fixed_mtime {
+                        Some(mtime) => Some(std::time::UNIX_EPOCH + Duration::from_secs(mtime)),
+                        None => fs::metadata(&entry.abs_path).and_then(|m| m.modified()).ok(),
+                    };
+                    if let Some(mtime) = mtime {
+                        File::options()
+                            .write(true)
+                            .open(&dest_path)
+                            .and_then(|f| f.set_modified(mtime))
+                            .map_err(|e| staging_error(format!("Failed to set the time of {}", dest_path.display()), e))?;
+                    }
+                    
+                    bytes_copied += entry.size;
+                }
+
+                // Progress from 0% to 50% during copy, by entry count when there are no bytes to count
+                let progress = if total_size > 0 {
+                    (bytes_copied as f64 / total_size as f64 * 50.0) as u8
+                } else {
+                    ((index + 1) as f64 / entries.len() as f64 * 50.0) as u8
+                };
+
+                let now = Instant::now();
+                if progress > last_progress_percent || now.duration_since(last_update_time) >= PROGRESS_INTERVAL {
+                     emit_progress(app_handle, progress).map_err(|e| e.to_string())?;
+                     if last_status_entry != Some(entry.abs_path.as_path()) {
+                         emit_status(app_handle, format!("Copie: {}", entry.abs_path.file_name().and_then(|n| n.to_str()).unwrap_or("..."))).map_err(|e| e.to_string())?;
+                         last_status_entry = Some(entry.abs_path.as_path());
+                     }
+                     last_update_time = now;
+                     last_progress_percent = progress;
+                }
+            }
+
+            emit_progress(app_handle, 50).map_err(|e| e.to_string())?; // Stage 2: Copying complete
+            emit_progress(app_handle, 50).map_err(|e| e.to_string())?; // Stage 2: Copying complete
+            emit_status(app_handle, "Estimation de la durée de compression...").map_err(|e| e.to_string())?;
+            let estimate = estimate_7z_duration(&temp_dir_path, total_size);
+            emit_status(app_handle, "Compression de l'archive (cette étape peut être longue)...").map_err(|e| e.to_string())?;
+
+            let running = Arc::new(AtomicBool::new(true));
+            let running_clone = running.clone();
+            let app_for_thread = app_handle.clone();
+            let job = CurrentJob::get();
+
+            // The 7z writer reports no progress: the bar follows the time estimate from
+            // 50% to 95%, or creeps 1% every 500 ms without one
+            let ticker = std::thread::spawn(move || {
+                let _job = CurrentJob::enter(job);
+                let started = Instant::now();
+                let mut progress: u8 = 50;
+                let max_progress: u8 = 95;
+
+                while running_clone.load(Ordering::SeqCst) {
+                    let _ = emit_progress(&app_for_thread, progress);
+                    if let Some(estimate) = estimate {
+                        let status = match estimate.checked_sub(started.elapsed()) {
+                            Some(remaining) if !remaining.is_zero() => format!(
+                                "Compression de l'archive (environ {} restantes)...",
+                                format_remaining(remaining)
+                            ),
+                            _ => "Compression de l'archive (presque terminée)...".to_string(),
+                        };
+                        let _ = emit_status(&app_for_thread, status);
+                    }
+                    std::thread::park_timeout(Duration::from_millis(500));
+                    progress = match estimate {
+                        Some(estimate) => {
+                            let fraction = started.elapsed().as_secs_f64() / estimate.as_secs_f64().max(1e-3);
+                            50 + (fraction.min(1.0) * 45.0) as u8
+                        }
+                        None => (progress + 1).min(max_progress),
+                    };
+                }
+            });
+
+            let res = compress_7z_encrypted(
+                &temp_dir_path,
+                output_path,
+                password,
+                options.kdf_cycles_power.unwrap_or(DEFAULT_7Z_KDF_CYCLES_POWER),
+                match compression {
+                    Some(CompressionSpec::SevenZip(level)) => level,
+                    _ => None,
+                },
+                options.sync_every_mb,
+            );
+
+            // Stopped and joined so a late tick can't overwrite the final progress and status
+            running.store(false, Ordering::SeqCst);
+            ticker.thread().unpark();
+            let _ = ticker.join();
+            if let Err(e) = res {
+                let _ = std::fs::remove_file(output_path);
+                return Err(format!("Failed to write {}: {}", output_path, e));
+            }
+
+            emit_progress(app_handle, 100).map_err(|e| e.to_string())?; // Stage 3: Compression complete
+            emit_status(app_handle, "Terminé !").map_err(|e| e.to_string())?;
+
+            Ok(format!(
+                "Files encrypted successfully to: {}",
+                output_path
+            ))
+        }
+        _ => {
+            // A whole-archive container is built as a plain zip in temp, then sealed
+            let sealed = options.whole_archive
+                .then(|| app_temp_file(".zip"))
+                .transpose()
+                .map_err(|e| e.to_string())?;
+            let output_path_buf = sealed.as_ref().map_or(Path::new(&output_path), |f| f.path());
+            // The temp zip behind a container is thrown away after a crash anyway
+            let sync_every_mb = options.sync_every_mb.filter(|_| sealed.is_none());
+            let file = create_output(output_path_buf, sync_every_mb)
+                .map_err(|e| format!("Failed to create output file: {}", e))?;
+            let mut zip = ZipWriter::new(file);
+            
+            emit_status(app_handle, "Chiffrement en cours...").map_err(|e| e.to_string())?;
+
+            let plain_options: FullFileOptions<'_> = FileOptions::default()
+                .compression_method(CompressionMethod::Deflated);
+            let file_options = if options.whole_archive {
+                plain_options.clone()
+            } else {
+                zip_file_options(encryption_method, password)
+            };
+            let (file_options, plain_options) = match options.fixed_mtime {
+                Some(mtime) => (
+                    file_options.last_modified_time(zip_datetime_from_unix(mtime)),
+                    plain_options.last_modified_time(zip_datetime_from_unix(mtime)),
+                ),
+                None => (file_options, plain_options),
+            };
+            let (file_options, plain_options) = match compression {
+                Some(CompressionSpec::Zip(method, level)) => (
+                    file_options.compression_method(method.into()).compression_level(level),
+                    plain_options.compression_method(method.into()).compression_level(level),
+                ),
+                _ => (file_options, plain_options),
+            };
+
+            if options.force_zip64 == Some(false) {
+                // Legacy readers only: refuse up front rather than failing midway
+                let too_large = entries.iter().find(|e| e.size > ZIP64_SIZE_THRESHOLD);
+                if let Some(entry) = too_large {
+                    let _ = std::fs::remove_file(output_path_buf);
+                    return Err(format!(
+                        "{} exceeds the 4 GB zip limit and Zip64 is disabled",
+                        entry.abs_path.display()
+                    ));
+                }
+                if entries.len() > ZIP_MAX_ENTRIES {
+                    let _ = std::fs::remove_file(output_path_buf);
+                    return Err(format!(
+                        "Too many entries for a zip without Zip64 (limit: {})",
+                        ZIP_MAX_ENTRIES
+                    ));
+                }
+            }
+
+            let entry_options = |entry: &CollectedEntry| {
+                let rel_str = entry.rel_path.to_string_lossy().replace('\\', "/");
+                let mut entry_options = if matches_any_pattern(&options.plaintext_patterns, &rel_str) {
+                    plain_options.clone()
+                } else {
+                    file_options.clone()
+                };
+                if options.auto_store_incompressible && is_incompressible(entry) {
+                    auto_stored.fetch_add(1, Ordering::SeqCst);
+                    entry_options = entry_options.compression_method(CompressionMethod::Stored);
+                }
+                let Ok(metadata) = fs::symlink_metadata(&entry.abs_path) else {
+                    return entry_options;
+                };
+                let entry_options = if options.store_ownership {
+                    with_entry_owner(entry_options, &metadata)
+                } else {
+                    entry_options
+                };
+                if options.fixed_mtime.is_some() {
+                    return entry_options;
+                }
+                // Entries keep their own timestamps so extraction can restore them
+                with_entry_timestamps(entry_options, &metadata)
+            };
+
+            if options.parallel_workers > 1 {
+                let written = write_zip_parallel(
+                    app_handle,
+                    cancel_flag,
+                    &mut zip,
+                    &entries,
+                    options,
+                    &retry,
+                    &entry_options,
+                );
+                match written {
+                    Ok(changed) => size_changes = changed,
+                    Err(e) => {
+                        let _ = std::fs::remove_file(output_path_buf);
+                        return Err(e);
+                    }
+                }
+            } else {
+                let mut progress = ProgressTracker::bytes_or_entries(app_handle, total_size, entries.len());
+                // The file name only goes out again once a different entry is being written
+                let mut last_status_entry: Option<&Path> = None;
+
+                for entry in &entries {
+                    if cancel_flag.load(Ordering::SeqCst) {
+                        let _ = std::fs::remove_file(output_path_buf);
+                        return Err("Encryption cancelled by user.".to_string());
+                    }
+
+                    let written = write_zip_entry(&mut zip, entry, entry_options(entry), options, cancel_flag, &retry, |bytes| {
+                        if progress.advance(bytes) && last_status_entry != Some(entry.abs_path.as_path()) {
+                            emit_status(app_handle, format!("Chiffrement: {}", entry.abs_path.file_name().and_then(|n| n.to_str()).unwrap_or("..."))).map_err(|e| e.to_string())?;
+                            last_status_entry = Some(entry.abs_path.as_path());
+                        }
+                        Ok(())
+                    });
+                    if cancel_flag.load(Ordering::SeqCst) {
+                        let _ = std::fs::remove_file(output_path_buf);
+                        return Err("Encryption cancelled by user.".to_string());
+                    }
+                    let written = match written {
+                        Ok(written) => written,
+                        Err(e) => {
+                            let _ = std::fs::remove_file(output_path_buf);
+                            return Err(e);
+                        }
+                    };
+                    if size_changed(entry, written) {
+                        size_changes.push(entry.abs_path.display().to_string());
+                    }
+                    progress.finish_entry();
+                }
+            }
+
+            if let Some(names) = &real_names {
+                let data = serde_json::to_vec(names).map_err(|e| e.to_string())?;
+                let written = zip.start_file(NAME_MANIFEST_ENTRY, file_options.clone())
+                    .and_then(|_| zip.write_all(&data).map_err(Into::into));
+                if let Err(e) = written {
+                    let _ = std::fs::remove_file(output_path_buf);
+                    return Err(format!("Failed to write name manifest: {}", e));
+                }
+            }
+
+            if let Some(duplicates) = duplicates.as_ref().filter(|d| !d.is_empty()) {
+                let data = serde_json::to_vec(duplicates).map_err(|e| e.to_string())?;
+                let written = zip.start_file(DEDUP_MANIFEST_ENTRY, file_options.clone())
+                    .and_then(|_| zip.write_all(&data).map_err(Into::into));
+                if let Err(e) = written {
+                    let _ = std::fs::remove_file(output_path_buf);
+                    return Err(format!("Failed to write duplicates manifest: {}", e));
+                }
+            }
+
+            if let Some(hint) = &options.password_hint {
+                zip.set_comment(format!("{}{}", PASSWORD_HINT_LABEL, hint.trim()));
+            }
+
+            // Writing the central directory takes a while with many entries, say so
+            // instead of leaving the bar parked on its last value
+            emit_status(app_handle, "Finalisation de l'archive...").map_err(|e| e.to_string())?;
+            let finished = zip.finish()
+                .map_err(|e| e.to_string())
+                .and_then(|file| finish_output(file).map_err(|e| e.to_string()));
+            let finished = finished.and_then(|_| add_unicode_path_fields(output_path_buf));
+            if let Err(e) = finished {
+                let _ = std::fs::remove_file(output_path_buf);
+                return Err(format!("Failed to finish zip {}: {}", output_path, e));
+            }
+            if options.pad_to.is_some() || options.random_padding {
+                if let Err(e) = pad_zip(output_path_buf, file_options, options) {
+                    let _ = std::fs::remove_file(output_path_buf);
+                    return Err(format!("Failed to pad {}: {}", output_path, e));
+                }
+            }
+            if sealed.is_some() {
+                emit_status(app_handle, "Chiffrement de l'archive complète...").map_err(|e| e.to_string())?;
+                if let Err(e) = seal_container(cancel_flag, output_path_buf, Path::new(output_path), password, options.sync_every_mb) {
+                    let _ = std::fs::remove_file(output_path);
+                    return Err(e);
+                }
+            }
+            // The smoothed bar trails the real value, close it explicitly
+            emit_progress(app_handle, 100).map_err(|e| e.to_string())?;
+            emit_status(app_handle, "Terminé !").map_err(|e| e.to_string())?;
+
+            Ok(format!(
+                "Files encrypted successfully to: {}",
+                output_path
+            ))
+        }
+    };
+    let message = message?;
+
+    if !size_changes.is_empty() {
+        log::warn!("Files changed size while being archived: {}", size_changes.join(", "));
+        emit_status(
+            app_handle,
+            format!("Attention : taille modifiée pendant l'archivage ({})", size_changes.join(", ")),
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    if let Some(manifest) = manifest {
+        let data = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+        fs::write(format!("{}.manifest.json", output_path), data)
+            .map_err(|e| format!("Failed to write manifest: {}", e))?;
+    }
+
+    if options.write_checksum_sidecar {
+        write_checksum_sidecar(app_handle, cancel_flag, Path::new(output_path), options.checksum_algorithm)?;
+    }
+
+    let stats = if options.collect_stats {
+        Some(read_entry_stats(Path::new(&output_path), password)?)
+    } else {
+        None
+    };
+
+    let retries = retry.retries.load(Ordering::SeqCst);
+    if retries > 0 {
+        log::info!("{} transient I/O errors recovered by retrying", retries);
+    }
+
+    if skipped_by_depth > 0 {
+        log::info!("{} entries below the depth limit were left out", skipped_by_depth);
+    }
+    if skipped_by_size.files > 0 {
+        log::info!(
+            "{} files ({} bytes) outside the size limits were left out",
+            skipped_by_size.files, skipped_by_size.bytes
+        );
+    }
+
+    let auto_stored = auto_stored.into_inner();
+    if auto_stored > 0 {
+        log::info!("{} incompressible files stored without compression", auto_stored);
+    }
+
+    Ok(EncryptionSummary {
+        message,
+        stats,
+        retries,
+        size_changed: size_changes,
+        skipped_by_depth,
+        auto_stored,
+        skipped_by_size,
+    })
+}
+
+// Everything a recurring job needs except the secret: passwords and keyfiles
+// are never part of a profile
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EncryptionSettings {
+    encryption_method: EncryptionMethod,
+    #[serde(default)]
+    options: EncryptOptions,
+}
+
+fn profile_path<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, name: &str) -> Result<std::path::PathBuf, String> {
+    // The name becomes a file name, keep it to a safe character set
+    let valid = !name.trim().is_empty()
+        && name.len() <= 64
+        && name.chars().all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'));
+    if !valid {
+        return Err("Profile names may only contain letters, digits, spaces, '-' and '_'".to_string());
+    }
+    let dir = app_handle.path().app_data_dir().map_err(|e| e.to_string())?.join("profiles");
+    Ok(dir.join(format!("{}.json", name.trim())))
+}
+
+fn read_profile<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, name: &str) -> Result<EncryptionSettings, String> {
+    let path = profile_path(app_handle, name)?;
+    let data = fs::read_to_string(&path).map_err(|_| format!("Profile not found: {}", name))?;
+    serde_json::from_str(&data).map_err(|e| format!("Invalid profile {}: {}", name, e))
+}
+
+// Options the caller sent are laid over the profile's one by one: only the fields present
+// in the call replace the saved values, everything else keeps what the profile says
+fn apply_profile(
+    settings: EncryptionSettings,
+    encryption_method: Option<EncryptionMethod>,
+    overrides: serde_json::Value,
+) -> Result<(EncryptionMethod, EncryptOptions), String> {
+    let mut merged = serde_json::to_value(&settings.options).map_err(|e| e.to_string())?;
+    if let (Some(merged), serde_json::Value::Object(overrides)) = (merged.as_object_mut(), overrides) {
+        merged.extend(overrides);
+    }
+    let options = serde_json::from_value(merged).map_err(|e| format!("Invalid options: {}", e))?;
+    Ok((encryption_method.unwrap_or(settings.encryption_method), options))
+}
+
+#[tauri::command]
+fn save_profile(app_handle: tauri::AppHandle, name: String, settings: EncryptionSettings) -> Result<(), String> {
+    let path = profile_path(&app_handle, &name)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let data = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+    fs::write(&path, data).map_err(|e| format!("Failed to save profile: {}", e))
+}
+
+#[tauri::command]
+fn load_profile(app_handle: tauri::AppHandle, name: String) -> Result<EncryptionSettings, String> {
+    read_profile(&app_handle, &name)
+}
+
+#[tauri::command]
+fn list_profiles(app_handle: tauri::AppHandle) -> Result<Vec<String>, String> {
+    let dir = app_handle.path().app_data_dir().map_err(|e| e.to_string())?.join("profiles");
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.to_string()),
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| path.file_stem().and_then(|s| s.to_str()).map(str::to_string))
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+const KEYRING_SERVICE: &str = "EaZip";
+
+// Remembered passwords live in the OS credential store (Keychain, Credential
+// Manager, Secret Service), keyed by the archive's canonical path
+fn keyring_entry(archive_id: &str) -> Result<keyring::Entry, String> {
+    let account = Path::new(archive_id)
+        .canonicalize()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| archive_id.to_string());
+    keyring::Entry::new(KEYRING_SERVICE, &account).map_err(|e| format!("Credential store unavailable: {}", e))
+}
+
+#[tauri::command]
+fn store_archive_password(archive_id: String, password: Secret<String>) -> Result<(), String> {
+    keyring_entry(&archive_id)?
+        .set_password(password.expose_secret())
+        .map_err(|e| format!("Failed to store password: {}", e))
+}
+
+#[tauri::command]
+fn forget_archive_password(archive_id: String) -> Result<(), String> {
+    match keyring_entry(&archive_id)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(format!("Failed to remove password: {}", e)),
+    }
+}
+
+fn remembered_password(archive_id: &str) -> Result<String, String> {
+    match keyring_entry(archive_id)?.get_password() {
+        Ok(password) => Ok(password),
+        Err(keyring::Error::NoEntry) => Err("No password given and none remembered for this archive".to_string()),
+        Err(e) => Err(format!("Failed to read remembered password: {}", e)),
+    }
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn encrypt_files(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    file_paths: Vec<String>,
+    output_path: String,
+    password: Secret<String>,
+    // Required unless a profile provides it
+    encryption_method: Option<EncryptionMethod>,
+    // Kept as JSON so a profile can tell the fields actually set from the defaults
+    options: Option<serde_json::Value>,
+    job_id: Option<JobId>,
+) -> Result<EncryptionSummary, String> {
+    let job = state.jobs.start(JobKind::Encrypt, job_id)?;
+    let cancel_flag = job.cancel_flag();
+    let password = password.expose_secret().clone(); // Clone password string
+    let options = options.unwrap_or_default();
+    let (encryption_method, options) = match options.get("profile").and_then(|p| p.as_str()) {
+        Some(name) => {
+            let settings = read_profile(&app_handle, name)?;
+            apply_profile(settings, encryption_method, options)?
+        }
+        None => {
+            let encryption_method = encryption_method.ok_or("No encryption method given")?;
+            let options = if options.is_null() {
+                EncryptOptions::default()
+            } else {
+                serde_json::from_value(options).map_err(|e| format!("Invalid options: {}", e))?
+            };
+            (encryption_method, options)
+        }
+    };
+
+    if let Some(policy) = &options.password_policy {
+        validate_password(&password, policy)?;
+    }
+    if matches!(encryption_method, EncryptionMethod::SevenZip) && !options.plaintext_patterns.is_empty() {
+        // 7z encrypts the whole archive with a single key, there is no per-entry switch
+        return Err("Plaintext entries are only supported for zip archives".to_string());
+    }
+    if options.obfuscate_names {
+        if matches!(encryption_method, EncryptionMethod::SevenZip) {
+            // 7z already hides names with header encryption
+            return Err("Name obfuscation is only supported for zip archives".to_string());
+        }
+        if !options.plaintext_patterns.is_empty() {
+            return Err("Name obfuscation cannot be combined with plaintext entries".to_string());
+        }
+        // The incremental manifest is a plain JSON file next to the archive, it would list the real names
+        if options.previous_manifest.is_some() {
+            return Err("Incremental archiving cannot be combined with name obfuscation".to_string());
+        }
+    }
+    if options.deduplicate {
+        if matches!(encryption_method, EncryptionMethod::SevenZip) {
+            return Err("Deduplication is only supported for zip archives".to_string());
+        }
+        // The duplicates manifest refers to the real entry names
+        if options.obfuscate_names {
+            return Err("Deduplication cannot be combined with name obfuscation".to_string());
+        }
+    }
+    if let Some(power) = options.kdf_cycles_power {
+        if !matches!(encryption_method, EncryptionMethod::SevenZip) {
+            log::warn!("Key derivation cost only applies to 7z, zip keeps its fixed 1000 iterations");
+        } else if !(MIN_7Z_KDF_CYCLES_POWER..=MAX_7Z_KDF_CYCLES_POWER).contains(&power) {
+            return Err(format!(
+                "Key derivation cost must be between {} and {}",
+                MIN_7Z_KDF_CYCLES_POWER, MAX_7Z_KDF_CYCLES_POWER
+            ));
+        }
+    }
+    if let Some(hint) = &options.password_hint {
+        if matches!(encryption_method, EncryptionMethod::SevenZip) {
+            // The 7z writer has no archive comment to put it in
+            return Err("Password hints are only supported for zip archives".to_string());
+        }
+        if hint.trim().is_empty() || hint.contains(password.as_str()) {
+            return Err("The password hint must not be empty or contain the password".to_string());
+        }
+        log::warn!("Storing a password hint in clear text in the archive comment");
+    }
+    if options.pad_to.is_some() || options.random_padding {
+        if matches!(encryption_method, EncryptionMethod::SevenZip) {
+            return Err("Padding is only supported for zip archives".to_string());
+        }
+        if options.pad_to == Some(0) {
+            return Err("The padding block size must be greater than 0".to_string());
+        }
+    }
+    if let (Some(min), Some(max)) = (options.min_size, options.max_size) {
+        if min > max {
+            return Err("The minimum file size is larger than the maximum".to_string());
+        }
+    }
+    if options.sync_every_mb == Some(0) {
+        return Err("The sync interval must be at least 1 MB".to_string());
+    }
+    if options.whole_archive {
+        if matches!(encryption_method, EncryptionMethod::SevenZip) {
+            return Err("Whole-archive encryption is only supported for zip archives".to_string());
+        }
+        // Nothing inside the container is readable without the password, these would be lost
+        if !options.plaintext_patterns.is_empty() || options.password_hint.is_some() {
+            return Err("Whole-archive encryption cannot be combined with plaintext entries or a password hint".to_string());
+        }
+        if options.collect_stats {
+            return Err("Entry statistics are not available for whole-archive encryption".to_string());
+        }
+    }
+    if let Some(spec) = &options.compression {
+        match (parse_compression_spec(spec)?, &encryption_method) {
+            (CompressionSpec::SevenZip(_), EncryptionMethod::SevenZip) => {}
+            (CompressionSpec::SevenZip(_), _) => return Err("7z compression needs the 7z format".to_string()),
+            (CompressionSpec::Zip(..), EncryptionMethod::SevenZip) => {
+                return Err("This compression method is only supported for zip archives".to_string());
+            }
+            (CompressionSpec::Zip(..), _) => {}
+        }
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let _job = job.enter();
+
+        if !options.separate_archives {
+            return encrypt_to_path(&app_handle, &cancel_flag, &file_paths, &output_path, &password, &encryption_method, &options);
+        }
+
+        let output_dir = Path::new(&output_path);
+        fs::create_dir_all(output_dir).map_err(|e| format!("Failed to create output directory: {}", e))?;
+        let extension = match encryption_method {
+            EncryptionMethod::SevenZip => ".7z",
+            _ if options.whole_archive => ".eazip",
+            _ => ".zip",
+        };
+
+        // Rendered up front so a bad template fails before any archive is written
+        let now = chrono::Local::now();
+        let names = file_paths
+            .iter()
+            .enumerate()
+            .map(|(i, path)| render_filename_template(&options.filename_template, path, i + 1, &now, extension))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut stats: Option<Vec<EntryStats>> = None;
+        let mut retries = 0;
+        let mut size_changed = Vec::new();
+        let mut skipped_by_depth = 0;
+        let mut auto_stored = 0;
+        let mut skipped_by_size = SkippedBySize::default();
+        for (file_path, name) in file_paths.iter().zip(&names) {
+            let target = unique_output_path(output_dir, name);
+            let target = target.to_str().ok_or("Invalid path encoding")?;
+            let summary = encrypt_to_path(
+                &app_handle,
+                &cancel_flag,
+                std::slice::from_ref(file_path),
+                target,
+                &password,
+                &encryption_method,
+                &options,
+            )?;
+            if let Some(entry_stats) = summary.stats {
+                stats.get_or_insert_with(Vec::new).extend(entry_stats);
+            }
+            retries += summary.retries;
+            size_changed.extend(summary.size_changed);
+            skipped_by_depth += summary.skipped_by_depth;
+            auto_stored += summary.auto_stored;
+            skipped_by_size.files += summary.skipped_by_size.files;
+            skipped_by_size.bytes += summary.skipped_by_size.bytes;
+        }
+
+        Ok(EncryptionSummary {
+            message: format!("{} archives created in: {}", names.len(), output_dir.display()),
+            stats,
+            retries,
+            size_changed,
+            skipped_by_depth,
+            auto_stored,
+            skipped_by_size,
+        })
+    }).await.map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+async fn encrypt_bytes(
+    data: Vec<u8>,
+    entry_name: String,
+    output_path: String,
+    password: Secret<String>,
+    encryption_method: EncryptionMethod,
+) -> Result<String, String> {
+    let password = password.expose_secret().clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        // The entry name ends up inside the archive, keep it a plain relative path
+        let name_path = Path::new(&entry_name);
+        if entry_name.is_empty()
+            || name_path.is_absolute()
+            || name_path.components().any(|c| !matches!(c, std::path::Component::Normal(_)))
+        {
+            return Err(format!("Invalid entry name: {}", entry_name));
+        }
+
+        match encryption_method {
+            EncryptionMethod::SevenZip => {
+                let mut writer = sevenz_rust2::ArchiveWriter::create(&output_path)
+                    .map_err(|e| format!("Failed to create output file: {}", e))?;
+                if !password.is_empty() {
+                    writer.set_content_methods(vec![
+                        seven_zip_aes_options(password.as_str(), DEFAULT_7Z_KDF_CYCLES_POWER).into(),
+                        sevenz_rust2::EncoderMethod::LZMA2.into(),
+                    ]);
+                }
+                writer
+                    .push_archive_entry(sevenz_rust2::ArchiveEntry::new_file(&entry_name), Some(data.as_slice()))
+                    .map_err(|e| {
+                        let _ = std::fs::remove_file(&output_path);
+                        format!("Failed to write to 7z: {}", e)
+                    })?;
+                writer.finish().map_err(|e| {
+                    let _ = std::fs::remove_file(&output_path);
+                    format!("Failed to finish 7z: {}", e)
+                })?;
+            }
+            _ => {
+                let file = File::create(&output_path)
+                    .map_err(|e| format!("Failed to create output file: {}", e))?;
+                let mut zip = ZipWriter::new(file);
+                let options = zip_file_options(&encryption_method, &password);
+
+                let res = zip
+                    .start_file(entry_name.as_str(), options)
+                    .map_err(|e| format!("Failed to start file in zip: {}", e))
+                    .and_then(|_| {
+                        zip.write_all(&data)
+                            .map_err(|e| format!("Failed to write to zip: {}", e))
+                    })
+                    .and_then(|_| {
+                        zip.finish()
+                            .map(|_| ())
+                            .map_err(|e| format!("Failed to finish zip: {}", e))
+                    });
+                if let Err(e) = res {
+                    let _ = std::fs::remove_file(&output_path);
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(format!("Data encrypted successfully to: {}", output_path))
+    }).await.map_err(|e| e.to_string())?
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NestedArchive {
+    // Relative to the top-level output directory
+    path: String,
+    error: Option<String>,
+    children: Vec<NestedArchive>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DecryptionSummary {
+    message: String,
+    nested: Option<Vec<NestedArchive>>,
+    // Targets left out by skip_unwritable
+    skipped_unwritable: Vec<String>,
+    // Damaged entries left out by skip_errors
+    failed_entries: Vec<String>,
+    // Extracted files whose stored owner couldn't be restored
+    not_chowned: Vec<String>,
+    // Files left out by modified_after
+    skipped_older: usize,
+}
+
+const DEFAULT_NESTED_DEPTH: usize = 3;
+const MAX_NESTED_DEPTH: usize = 10;
+
+fn has_archive_magic(path: &Path) -> bool {
+    let mut magic = [0u8; 6];
+    let read = File::open(path).and_then(|mut f| f.read_exact(&mut magic));
+    read.is_ok()
+        && (magic.starts_with(b"PK\x03\x04")
+            || magic == *b"7z\xBC\xAF\x27\x1C"
+            || magic == *b"Rar!\x1A\x07")
+}
+
+struct NestedExtraction<'a, R: tauri::Runtime> {
+    app_handle: &'a tauri::AppHandle<R>,
+    cancel_flag: &'a AtomicBool,
+    root: &'a Path,
+    // The archive the user opened, in case it sits inside the output directory
+    source: std::path::PathBuf,
+    passwords: Vec<String>,
+    options: &'a DecryptOptions,
+    // Bytes and files written so far, by the outer archive and every nested one
+    spent_size: u64,
+    spent_files: usize,
+}
+
+impl<R: tauri::Runtime> NestedExtraction<'_, R> {
+    // Counts files an extraction wrote against the limits shared by all levels
+    fn spend(&mut self, written: &[std::path::PathBuf]) {
+        self.spent_files += written.len();
+        let size = written.iter().filter_map(|p| fs::metadata(p).ok()).fold(0u64, |acc, m| acc.saturating_add(m.len()));
+        self.spent_size = self.spent_size.saturating_add(size);
+    }
+
+    // Archives among the files an extraction just wrote go to a sibling folder named
+    // after them, then what that writes is searched in turn until `depth` runs out.
+    // Whatever already sat in the output folder is never opened.
+    fn extract_in(&mut self, written: &[std::path::PathBuf], depth: usize) -> Result<Vec<NestedArchive>, String> {
+        if depth == 0 {
+            return Ok(Vec::new());
+        }
+
+        let candidates: Vec<&std::path::PathBuf> = written
+            .iter()
+            .filter(|p| p.is_file() && has_archive_magic(p))
+            .filter(|p| p.canonicalize().map_or(true, |c| c != self.source))
+            .collect();
+
+        let mut found = Vec::new();
+        for archive in candidates {
+            if self.cancel_flag.load(Ordering::SeqCst) {
+                return Err("Decryption cancelled by user.".to_string());
+            }
+
+            let parent = archive.parent().unwrap_or(self.root);
+            let stem = archive.file_stem().and_then(|s| s.to_str()).unwrap_or("archive");
+            let target = unique_output_path(parent, stem);
+            let relative = archive.strip_prefix(self.root).unwrap_or(archive).to_string_lossy().replace('\\', "/");
+            emit_status(self.app_handle, format!("Archive imbriquée : {}", relative)).map_err(|e| e.to_string())?;
+
+            let mut error = None;
+            let mut extracted = Vec::new();
+            for password in &self.passwords {
+                fs::create_dir_all(&target).map_err(|e| e.to_string())?;
+                let target_str = target.to_str().ok_or("Invalid path encoding")?;
+                let mut skipped = SkippedEntries::new(self.options);
+                skipped.spent_size = self.spent_size;
+                skipped.spent_files = self.spent_files;
+                match extract_archive(self.app_handle, self.cancel_flag, archive, target_str, password, self.options, &mut skipped) {
+                    Ok(_) => {
+                        error = None;
+                        extracted = skipped.written;
+                        break;
+                    }
+                    Err(e) => {
+                        // Don't leave half an extraction behind before the next attempt
+                        let _ = fs::remove_dir_all(&target);
+                        if self.cancel_flag.load(Ordering::SeqCst) {
+                            return Err(e);
+                        }
+                        error = Some(e);
+                    }
+                }
+            }
+
+            let children = if error.is_none() {
+                self.spend(&extracted);
+                self.extract_in(&extracted, depth - 1)?
+            } else {
+                Vec::new()
+            };
+            found.push(NestedArchive { path: relative, error, children });
+        }
+
+        Ok(found)
+    }
+}
+
+// A multi-part archive picked by any of its parts. `name.7z.001`-style sets are plain
+// byte splits, `name.z01 ... name.zip` is a PKZIP split whose offsets need fixing up.
+struct SplitArchive {
+    parts: Vec<std::path::PathBuf>,
+    // Of the joined file, so the usual extension-based format detection still applies
+    extension: &'static str,
+    pkzip: bool,
+}
+
+fn missing_parts_error(missing: &[std::path::PathBuf]) -> String {
+    let names: Vec<String> = missing
+        .iter()
+        .map(|p| p.file_name().unwrap_or_default().to_string_lossy().into_owned())
+        .collect();
+    format!("Missing parts of split archive: {}", names.join(", "))
+}
+
+// Disk number of the last part, read from the end of central directory record
+fn zip_last_disk(path: &Path) -> Result<u16, String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let len = file.seek(SeekFrom::End(0)).map_err(|e| e.to_string())?;
+    // 22-byte record plus a comment of up to 64 KB
+    let tail_len = len.min(22 + u16::MAX as u64);
+    file.seek(SeekFrom::Start(len - tail_len)).map_err(|e| e.to_string())?;
+    let mut tail = vec![0; tail_len as usize];
+    file.read_exact(&mut tail).map_err(|e| e.to_string())?;
+    let pos = tail
+        .windows(4)
+        .rposition(|w| w == b"PK\x05\x06")
+        .ok_or("Invalid split zip: end of central directory not found")?;
+    Ok(u16::from_le_bytes([tail[pos + 4], tail[pos + 5]]))
+}
+
+fn detect_split_archive(path: &Path) -> Result<Option<SplitArchive>, String> {
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    let Some((stem, extension)) = file_name.rsplit_once('.') else {
+        return Ok(None);
+    };
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let extension = extension.to_ascii_lowercase();
+
+    if extension.len() == 3 && extension.bytes().all(|b| b.is_ascii_digit()) {
+        // Numbered parts: the highest one found tells how many there should be
+        let prefix = format!("{}.", stem);
+        let mut last = 0;
+        for entry in fs::read_dir(dir).map_err(|e| e.to_string())? {
+            let name = entry.map_err(|e| e.to_string())?.file_name();
+            let number = name.to_str().and_then(|n| n.strip_prefix(&prefix)).filter(|n| n.len() == 3);
+            if let Some(number) = number.and_then(|n| n.parse::<u32>().ok()) {
+                last = last.max(number);
+            }
+        }
+        let parts: Vec<_> = (1..=last).map(|n| dir.join(format!("{}.{:03}", stem, n))).collect();
+        let missing: Vec<_> = parts.iter().filter(|p| !p.exists()).cloned().collect();
+        if !missing.is_empty() {
+            return Err(missing_parts_error(&missing));
+        }
+        let extension = if stem.to_ascii_lowercase().ends_with(".7z") { "7z" } else { "zip" };
+        return Ok(Some(SplitArchive { parts, extension, pkzip: false }));
+    }
+
+    let is_zip_part = extension.len() >= 3 && extension.starts_with('z') && extension[1..].bytes().all(|b| b.is_ascii_digit());
+    let is_split_zip = is_zip_part || (extension == "zip" && dir.join(format!("{}.z01", stem)).exists());
+    if !is_split_zip {
+        return Ok(None);
+    }
+    let zip_path = dir.join(format!("{}.zip", stem));
+    if !zip_path.exists() {
+        return Err(missing_parts_error(&[zip_path]));
+    }
+    let last_disk = zip_last_disk(&zip_path)?;
+    let mut parts: Vec<_> = (1..=last_disk).map(|n| dir.join(format!("{}.z{:02}", stem, n))).collect();
+    let missing: Vec<_> = parts.iter().filter(|p| !p.exists()).cloned().collect();
+    if !missing.is_empty() {
+        return Err(missing_parts_error(&missing));
+    }
+    parts.push(zip_path);
+    Ok(Some(SplitArchive { parts, extension: "zip", pkzip: true }))
+}
+
+// Info-ZIP Unicode Path field (0x7075) for every non-ASCII name: version 1, CRC-32 of the
+// name as stored, then the name in UTF-8. Such names are already UTF-8 with flag bit 11,
+// but unzip builds without UTF-8 flag support only trust this field. The zip crate won't
+// write it (its check compares the CRC with an empty name), so it goes into the central
+// directory of the finished archive, the copy unzip reads. Reading it back is done by
+// the zip crate itself. Zip64 archives are left as they are.
+fn add_unicode_path_fields(path: &Path) -> Result<(), String> {
+    let mut file = File::options().read(true).write(true).open(path).map_err(|e| e.to_string())?;
+    let len = file.seek(SeekFrom::End(0)).map_err(|e| e.to_string())?;
+    let tail_len = len.min(22 + u16::MAX as u64);
+    file.seek(SeekFrom::Start(len - tail_len)).map_err(|e| e.to_string())?;
+    let mut tail = vec![0; tail_len as usize];
+    file.read_exact(&mut tail).map_err(|e| e.to_string())?;
+    let pos = tail
+        .windows(4)
+        .rposition(|w| w == b"PK\x05\x06")
+        .ok_or("End of central directory not found")?;
+    let zip64_locator = pos >= 20 && &tail[pos - 20..pos - 16] == b"PK\x06\x07";
+    let mut eocd = tail.split_off(pos);
+    let total_entries = u16::from_le_bytes([eocd[10], eocd[11]]);
+    let cd_size = u32::from_le_bytes([eocd[12], eocd[13], eocd[14], eocd[15]]);
+    let cd_offset = u32::from_le_bytes([eocd[16], eocd[17], eocd[18], eocd[19]]);
+    if zip64_locator || total_entries == u16::MAX || cd_offset == u32::MAX {
+        return Ok(());
+    }
+
+    let mut cd = vec![0; cd_size as usize];
+    file.seek(SeekFrom::Start(cd_offset as u64)).map_err(|e| e.to_string())?;
+    file.read_exact(&mut cd).map_err(|e| e.to_string())?;
+    let mut rebuilt = Vec::with_capacity(cd.len());
+    let mut at = 0;
+    for _ in 0..total_entries {
+        let header = cd.get(at..at + 46).ok_or("Truncated central directory")?;
+        if &header[0..4] != b"PK\x01\x02" {
+            return Err("Bad central directory entry".to_string());
+        }
+        let [name_len, extra_len, comment_len] = [28, 30, 32].map(|i| u16::from_le_bytes([header[i], header[i + 1]]) as usize);
+        let record = cd.get(at..at + 46 + name_len + extra_len + comment_len).ok_or("Truncated central directory")?;
+        at += record.len();
+        let name = &record[46..46 + name_len];
+        let extra_end = 46 + name_len + extra_len;
+        let mut extra = &record[46 + name_len..extra_end];
+        let mut has_unicode_path = false;
+        while extra.len() >= 4 {
+            has_unicode_path |= extra[0..2] == 0x7075u16.to_le_bytes();
+            extra = extra.get(4 + u16::from_le_bytes([extra[2], extra[3]]) as usize..).unwrap_or_default();
+        }
+        let new_extra_len = extra_len + 9 + name_len;
+        if name.is_ascii() || has_unicode_path || std::str::from_utf8(name).is_err() || new_extra_len > u16::MAX as usize {
+            rebuilt.extend_from_slice(record);
+            continue;
+        }
+        rebuilt.extend_from_slice(&record[..30]);
+        rebuilt.extend_from_slice(&(new_extra_len as u16).to_le_bytes());
+        rebuilt.extend_from_slice(&record[32..extra_end]);
+        rebuilt.extend_from_slice(&0x7075u16.to_le_bytes());
+        rebuilt.extend_from_slice(&(5 + name_len as u16).to_le_bytes());
+        rebuilt.push(1);
+        rebuilt.extend_from_slice(&crc32fast::hash(name).to_le_bytes());
+        rebuilt.extend_from_slice(name);
+        rebuilt.extend_from_slice(&record[extra_end..]);
+    }
+    if rebuilt.len() == cd.len() {
+        return Ok(());
+    }
+
+    let Ok(new_cd_size) = u32::try_from(rebuilt.len()) else {
+        return Ok(());
+    };
+    eocd[12..16].copy_from_slice(&new_cd_size.to_le_bytes());
+    file.seek(SeekFrom::Start(cd_offset as u64)).map_err(|e| e.to_string())?;
+    file.write_all(&rebuilt).map_err(|e| e.to_string())?;
+    file.write_all(&eocd).map_err(|e| e.to_string())?;
+    file.set_len(cd_offset as u64 + rebuilt.len() as u64 + eocd.len() as u64).map_err(|e| e.to_string())?;
+    file.sync_all().map_err(|e| e.to_string())
+}
+
+// The central directory of a PKZIP split stores offsets relative to each part; once
+// joined they become absolute and every disk number 0, which is all the zip reader handles
+fn merge_split_zip_offsets(file: &mut File, part_sizes: &[u64]) -> Result<(), String> {
+    let zip64 = || "Split Zip64 archives are not supported".to_string();
+    let starts: Vec<u64> = part_sizes
+        .iter()
+        .scan(0u64, |offset, size| {
+            let start = *offset;
+            *offset += size;
+            Some(start)
+        })
+        .collect();
+    let part_start = |disk: u16| starts.get(disk as usize).copied().ok_or("Invalid split zip: unknown part number");
+
+    let len = file.seek(SeekFrom::End(0)).map_err(|e| e.to_string())?;
+    let tail_len = len.min(22 + u16::MAX as u64);
+    file.seek(SeekFrom::Start(len - tail_len)).map_err(|e| e.to_string())?;
+    let mut tail = vec![0; tail_len as usize];
+    file.read_exact(&mut tail).map_err(|e| e.to_string())?;
+    let pos = tail
+        .windows(4)
+        .rposition(|w| w == b"PK\x05\x06")
+        .ok_or("Invalid split zip: end of central directory not found")?;
+    let eocd = &mut tail[pos..pos + 22];
+
+    let cd_disk = u16::from_le_bytes([eocd[6], eocd[7]]);
+    let total_entries = u16::from_le_bytes([eocd[10], eocd[11]]);
+    let cd_size = u32::from_le_bytes([eocd[12], eocd[13], eocd[14], eocd[15]]);
+    let cd_offset = u32::from_le_bytes([eocd[16], eocd[17], eocd[18], eocd[19]]);
+    if total_entries == u16::MAX || cd_offset == u32::MAX {
+        return Err(zip64());
+    }
+    let cd_start = part_start(cd_disk)? + cd_offset as u64;
+    let cd_start_u32 = u32::try_from(cd_start).map_err(|_| zip64())?;
+
+    let mut cd = vec![0; cd_size as usize];
+    file.seek(SeekFrom::Start(cd_start)).map_err(|e| e.to_string())?;
+    file.read_exact(&mut cd).map_err(|e| e.to_string())?;
+    let mut at = 0;
+    for _ in 0..total_entries {
+        let header = cd.get_mut(at..at + 46).ok_or("Invalid split zip: truncated central directory")?;
+        if &header[0..4] != b"PK\x01\x02" {
+            return Err("Invalid split zip: bad central directory entry".to_string());
+        }
+        let disk = u16::from_le_bytes([header[34], header[35]]);
+        let offset = u32::from_le_bytes([header[42], header[43], header[44], header[45]]);
+        if disk == u16::MAX || offset == u32::MAX {
+            return Err(zip64());
+        }
+        let absolute = u32::try_from(part_start(disk)? + offset as u64).map_err(|_| zip64())?;
+        header[34..36].copy_from_slice(&0u16.to_le_bytes());
+        header[42..46].copy_from_slice(&absolute.to_le_bytes());
+        let variable = [28, 30, 32].iter().map(|&i| u16::from_le_bytes([header[i], header[i + 1]]) as usize).sum::<usize>();
+        at += 46 + variable;
+    }
+
+    eocd[4..8].copy_from_slice(&[0; 4]);
+    eocd[8..10].copy_from_slice(&total_entries.to_le_bytes());
+    eocd[16..20].copy_from_slice(&cd_start_u32.to_le_bytes());
+    file.seek(SeekFrom::Start(cd_start)).map_err(|e| e.to_string())?;
+    file.write_all(&cd).map_err(|e| e.to_string())?;
+    file.seek(SeekFrom::Start(len - tail_len + pos as u64)).map_err(|e| e.to_string())?;
+    file.write_all(&tail[pos..pos + 22]).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// Streams the parts, in order, into one temp archive the regular extraction can read
+fn join_split_archive<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    cancel_flag: &AtomicBool,
+    split: &SplitArchive,
+) -> Result<tempfile::NamedTempFile, String> {
+    emit_status(app_handle, format!("Assemblage de {} volumes...", split.parts.len())).map_err(|e| e.to_string())?;
+    let mut joined = app_temp_file(&format!(".{}", split.extension)).map_err(|e| e.to_string())?;
+    let mut part_sizes = Vec::with_capacity(split.parts.len());
+    for part in &split.parts {
+        if cancel_flag.load(Ordering::SeqCst) {
+            return Err("Decryption cancelled by user.".to_string());
+        }
+        let mut input = File::open(part).map_err(|e| format!("Failed to open {}: {}", part.display(), e))?;
+        part_sizes.push(std::io::copy(&mut input, joined.as_file_mut()).map_err(|e| e.to_string())?);
+    }
+    if split.pkzip {
+        merge_split_zip_offsets(joined.as_file_mut(), &part_sizes)?;
+    }
+    Ok(joined)
+}
+
+fn extract_archive<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    cancel_flag: &AtomicBool,
+    path: &Path,
+    output_dir: &str,
+    password: &str,
+    options: &DecryptOptions,
+    skipped: &mut SkippedEntries,
+) -> Result<String, String> {
+    if options.reject_absolute_paths {
+        // Checked up front so nothing is written from a refused archive
+        let entries = read_archive_entries(path, password)?;
+        if let Some(entry) = entries.iter().find(|e| is_unsafe_entry_name(&e.name)) {
+            return Err(format!("Archive refused, entry has an absolute or parent path: {}", entry.name));
+        }
+    }
+
+    if is_7z_path(path) {
+        if options.resume {
+            log::warn!("Resume is not supported for 7z archives, extracting everything");
+        }
+        emit_status(app_handle, "Déchiffrement 7z en cours...").map_err(|e| e.to_string())?;
+        extract_7z_guarded(
+            app_handle,
+            cancel_flag,
+            path,
+            Path::new(&output_dir),
+            password,
+            options,
+            skipped,
+        )?;
+    } else if is_rar_path(path) {
+        emit_status(app_handle, "Déchiffrement RAR en cours...").map_err(|e| e.to_string())?;
+        extract_rar_guarded(
+            app_handle,
+            cancel_flag,
+            path,
+            Path::new(&output_dir),
+            password,
+            options,
+            skipped,
+        )?;
+    } else {
+        emit_status(app_handle, "Ouverture de l'archive...").map_err(|e| e.to_string())?;
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        let mut archive = zip::ZipArchive::new(file).map_err(|e| zip_error_message(path, e))?;
+        // A wrong password is caught on the first encrypted file before anything else:
+        // folders usually come first unencrypted, and the size pass below decrypts
+        // every header, which takes a while on big archives
+        let first_encrypted = (0..archive.len()).find(|&i| archive.by_index_raw(i).is_ok_and(|f| f.encrypted() && !f.is_dir()));
+        if let Some(i) = first_encrypted {
+            archive.by_index_decrypt(i, password.as_bytes()).map_err(|e| zip_error_message(path, e))?;
+        }
+        let real_names = read_name_manifest(&mut archive, path, password)?;
+        let duplicates = read_dedup_manifest(&mut archive, path, password)?;
+        // Where each file went, for the duplicates to be copied from
+        let mut extracted = std::collections::HashMap::new();
+        let legacy_encoding = legacy_name_encoding(options.encoding.as_deref())?;
+
+        let mut progress = if options.low_memory {
+            ProgressTracker::by_entries(app_handle, archive.len())
+        } else {
+            emit_status(app_handle, "Calcul de la taille totale...").map_err(|e| e.to_string())?;
+            // Calculate total size for progress
+            let mut total_size: u64 = 0;
+            let len = archive.len();
+            for i in 0..len {
+                if cancel_flag.load(Ordering::SeqCst) {
+                    return Err("Decryption cancelled by user.".to_string());
+                }
+                if i % 50 == 0 {
+                    emit_status(app_handle, format!("Analyse du contenu... ({}/{})", i, len)).map_err(|e| e.to_string())?;
+                }
+
+                // We must use by_index_decrypt even for size calculation if the file is encrypted
+                let file = archive
+                    .by_index_decrypt(i, password.as_bytes())
+                    .map_err(|e| zip_error_message(path, e))?;
+                total_size += file.size();
+            }
+            ProgressTracker::new(app_handle, total_size)
+        };
+
+        let mut extracted_count: usize = 0;
+        let mut resumed_count: usize = 0;
+        let mut collisions = CaseCollisions::new(Path::new(&output_dir));
+        let mut dir_times = Vec::new();
+
+        emit_status(app_handle, "Déchiffrement en cours...").map_err(|e| e.to_string())?;
+
+        for i in 0..archive.len() {
+            if cancel_flag.load(Ordering::SeqCst) {
+                return Err("Decryption cancelled by user.".to_string());
+            }
+
+            let mut file = archive
+                .by_index_decrypt(i, password.as_bytes())
+                .map_err(|e| zip_error_message(path, e))?;
+            
+            // Zip Bomb Protection
+            extracted_count += 1;
+            if extracted_count > skipped.file_limit() {
+                return Err(format!("Too many files in archive (limit: {})", MAX_FILE_COUNT));
+            }
+
+            let size = file.size();
+            // We check the extracted size dynamically as we write, but checking here is good too
+            if progress.done + size > skipped.size_limit() {
+                 return Err(format!("Total extracted size exceeds limit (limit: {} bytes)", MAX_TOTAL_SIZE));
+            }
+            let max_ratio = options.max_compression_ratio.unwrap_or(DEFAULT_MAX_COMPRESSION_RATIO);
+            let ratio = size / file.compressed_size().max(1);
+            if max_ratio > 0 && size >= COMPRESSION_RATIO_MIN_SIZE && ratio > max_ratio {
+                return Err(format!(
+                    "Suspicious compression ratio for {} ({}:1, limit {}:1), possible zip bomb",
+                    file.name(),
+                    ratio,
+                    max_ratio
+                ));
+            }
+
+            // Filler from pad_zip and the duplicates list, not part of the content
+            if file.name() == PADDING_ENTRY || (duplicates.is_some() && file.name() == DEDUP_MANIFEST_ENTRY) {
+                progress.advance(size);
+                progress.finish_entry();
+                continue;
+            }
+
+            // Zip Slip Protection
+            let outpath = match &real_names {
+                Some(names) => {
+                    if file.name() == NAME_MANIFEST_ENTRY {
+                        progress.advance(size);
+                        progress.finish_entry();
+                        continue;
+                    }
+                    let real = names
+                        .get(file.name().trim_end_matches('/'))
+                        .ok_or_else(|| format!("No real name recorded for entry {}", file.name()))?;
+                    safe_entry_output_path(Path::new(&output_dir), real)?
+                }
+                None => match legacy_encoding.filter(|_| is_legacy_encoded_name(&file)) {
+                    Some(encoding) => {
+                        let (decoded, _, _) = encoding.decode(file.name_raw());
+                        safe_entry_output_path(Path::new(&output_dir), &decoded)?
+                    }
+                    None => Path::new(&output_dir).join(file.mangled_name()),
+                },
+            };
+            let canonical_output_dir = Path::new(&output_dir).canonicalize().map_err(|e| e.to_string())?;
+            
+            if !outpath.starts_with(output_dir) {
+                 return Err("Invalid file path (Zip Slip attempt detected)".to_string());
+            }
+            let outpath = map_output_path(Path::new(&output_dir), outpath, &options.path_map)?;
+
+            if file.is_dir() {
+                fs::create_dir_all(&outpath).map_err(|e| e.to_string())?;
+                if options.preserve_ownership {
+                    restore_owner(&outpath, zip_entry_owner(&file), skipped);
+                }
+                if let Some(mtime) = zip_entry_mtime(&file) {
+                    dir_times.push((outpath, mtime));
+                }
+            } else {
+                if let Some(p) = outpath.parent() {
+                    if !p.exists() {
+                        fs::create_dir_all(p).map_err(|e| e.to_string())?;
+                    }
+                    let canonical_parent = p.canonicalize().map_err(|e| e.to_string())?;
+                    if !canonical_parent.starts_with(&canonical_output_dir) {
+                         return Err("Invalid file path (Zip Slip attempt detected)".to_string());
+                    }
+                }
+
+                let outpath = match collisions.resolve(app_handle, outpath, options.case_collision_policy) {
+                    Some(outpath) if !skipped.is_older(zip_entry_mtime(&file)) => outpath,
+                    _ => {
+                        progress.advance(size);
+                        progress.finish_entry();
+                        continue;
+                    }
+                };
+
+                if options.resume && is_already_extracted(&outpath, file.size(), file.crc32()) {
+                    progress.advance(size);
+                    progress.finish_entry();
+                    resumed_count += 1;
+                    if duplicates.is_some() {
+                        extracted.insert(file.name().to_string(), outpath);
+                    }
+                    continue;
+                }
+                
+                let Some(mut outfile) = skipped.create(app_handle, &outpath)? else {
+                    progress.advance(size);
+                    progress.finish_entry();
+                    continue;
+                };
+
+                // Manual copy with progress, capped so a lying header can't go past the limit
+                let remaining = skipped.size_limit().saturating_sub(progress.done);
+                let written = copy_with_progress(
+                    &mut (&mut file).take(remaining.saturating_add(1)),
+                    &mut outfile,
+                    cancel_flag,
+                    &mut progress,
+                    "Decryption cancelled by user.",
+                )?;
+                if written > remaining {
+                    return Err(format!("Total extracted size exceeds limit (limit: {} bytes)", MAX_TOTAL_SIZE));
+                }
+                if let Some(mtime) = zip_entry_mtime(&file) {
+                    if let Err(e) = outfile.set_modified(mtime) {
+                        log::warn!("Could not restore timestamp of {}: {}", outpath.display(), e);
+                    }
+                }
+                if options.preserve_ownership {
+                    restore_owner(&outpath, zip_entry_owner(&file), skipped);
+                }
+                if duplicates.is_some() {
+                    extracted.insert(file.name().to_string(), outpath);
+                }
+            }
+            progress.finish_entry();
+        }
+
+        if let Some(duplicates) = &duplicates {
+            emit_status(app_handle, "Restauration des doublons...").map_err(|e| e.to_string())?;
+            restore_duplicates(Path::new(&output_dir), duplicates, &extracted, options, skipped, &mut extracted_count, progress.done)?;
+        }
+
+        if resumed_count > 0 {
+            log::info!("Resume: {} entries already extracted were skipped", resumed_count);
+        }
+        restore_dir_times(dir_times);
+    }
+
+    emit_progress(app_handle, 100).map_err(|e| e.to_string())?;
+    emit_status(app_handle, "Déchiffrement terminé !").map_err(|e| e.to_string())?;
+
+    if options.low_memory && !is_7z_path(path) && !is_rar_path(path) {
+        return Ok(format!(
+            "File decrypted successfully to: {} (low-memory mode: progress counted per entry, no total-size pass)",
+            output_dir
+        ));
+    }
+    Ok(format!("File decrypted successfully to: {}", output_dir))
+}
+
+#[tauri::command]
+async fn decrypt_file(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    file_path: String,
+    output_dir: String,
+    // Falls back to the password remembered with store_archive_password
+    password: Option<Secret<String>>,
+    options: Option<DecryptOptions>,
+    job_id: Option<JobId>,
+) -> Result<DecryptionSummary, String> {
+    let job = state.jobs.start(JobKind::Decrypt, job_id)?;
+    let cancel_flag = job.cancel_flag();
+    let password = match password {
+        Some(password) => password.expose_secret().clone(),
+        None => remembered_password(&file_path)?,
+    };
+    let options = options.unwrap_or_default();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let _job = job.enter();
+        let path = Path::new(&file_path);
+        // Split sets are joined into a temp file first, removed once extraction is done
+        let joined = match detect_split_archive(path)? {
+            Some(split) => Some(join_split_archive(&app_handle, &cancel_flag, &split)?),
+            None => None,
+        };
+        let archive_path = joined.as_ref().map_or(path, |f| f.path());
+        // Same for whole-archive containers, decrypted to the plain zip they hold
+        let opened = if is_container(archive_path) {
+            Some(open_container(&app_handle, &cancel_flag, archive_path, &password)?)
+        } else {
+            None
+        };
+        let archive_path = opened.as_ref().map_or(archive_path, |f| f.path());
+        let mut skipped = SkippedEntries::new(&options);
+        let message = extract_archive(&app_handle, &cancel_flag, archive_path, &output_dir, &password, &options, &mut skipped)?;
+        drop(opened);
+        drop(joined);
+
+        let nested = if options.extract_recursive {
+            let mut passwords = vec![password.clone()];
+            passwords.extend(options.nested_passwords.iter().map(|p| p.expose_secret().clone()));
+            let mut nested = NestedExtraction {
+                app_handle: &app_handle,
+                cancel_flag: &cancel_flag,
+                root: Path::new(&output_dir),
+                source: path.canonicalize().map_err(|e| e.to_string())?,
+                passwords,
+                options: &options,
+                spent_size: 0,
+                spent_files: 0,
+            };
+            nested.spend(&skipped.written);
+            let depth = options.max_nested_depth.unwrap_or(DEFAULT_NESTED_DEPTH).min(MAX_NESTED_DEPTH);
+            Some(nested.extract_in(&skipped.written, depth)?)
+        } else {
+            None
+        };
+
+        if !skipped.unwritable.is_empty() {
+            emit_status(&app_handle, format!("{} fichier(s) non inscriptible(s) ignoré(s)", skipped.unwritable.len())).map_err(|e| e.to_string())?;
+        }
+        if !skipped.failed.is_empty() {
+            emit_status(&app_handle, format!("{} fichier(s) endommagé(s) ignoré(s)", skipped.failed.len())).map_err(|e| e.to_string())?;
+        }
+        if skipped.older > 0 {
+            emit_status(&app_handle, format!("{} fichier(s) plus ancien(s) ignoré(s)", skipped.older)).map_err(|e| e.to_string())?;
+        }
+        if !skipped.not_chowned.is_empty() {
+            log::warn!("Could not restore the owner of {} file(s)", skipped.not_chowned.len());
+            emit_status(&app_handle, format!("Propriétaire non restauré pour {} fichier(s)", skipped.not_chowned.len())).map_err(|e| e.to_string())?;
+        }
+
+        Ok(DecryptionSummary {
+            message,
+            nested,
+            skipped_unwritable: skipped.unwritable,
+            failed_entries: skipped.failed,
+            not_chowned: skipped.not_chowned,
+            skipped_older: skipped.older,
+        })
+    }).await.map_err(|e| e.to_string())?
+}
+
+// Extracts into a temp folder (timestamps restored) and re-archives its top-level
+// entries, so the structure inside the archive stays the same
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn convert_archive(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    input: String,
+    output: String,
+    input_password: Secret<String>,
+    output_password: Secret<String>,
+    output_method: EncryptionMethod,
+    job_id: Option<JobId>,
+) -> Result<String, String> {
+    let job = state.jobs.start(JobKind::Decrypt, job_id)?;
+    let cancel_flag = job.cancel_flag();
+    let input_password = input_password.expose_secret().clone();
+    let output_password = output_password.expose_secret().clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let _job = job.enter();
+        let input_path = Path::new(&input);
+        if Path::new(&output).canonicalize().ok() == Some(input_path.canonicalize().map_err(|e| e.to_string())?) {
+            return Err("The converted archive can't replace its source".to_string());
+        }
+
+        let temp_dir = app_temp_dir().map_err(|e| e.to_string())?;
+        let temp_path = temp_dir.path().to_str().ok_or("Invalid path encoding")?;
+        let options = DecryptOptions::default();
+        extract_archive(&app_handle, &cancel_flag, input_path, temp_path, &input_password, &options, &mut SkippedEntries::new(&options))?;
+
+        let mut top_level = fs::read_dir(temp_dir.path())
+            .map_err(|e| e.to_string())?
+            .map(|entry| entry.map(|e| e.path().to_string_lossy().into_owned()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        if top_level.is_empty() {
+            return Err("The archive has no entries to convert".to_string());
+        }
+        top_level.sort();
+
+        let summary = encrypt_to_path(
+            &app_handle,
+            &cancel_flag,
+            &top_level,
+            &output,
+            &output_password,
+            &output_method,
+            &EncryptOptions::default(),
+        )?;
+        Ok(summary.message)
+    }).await.map_err(|e| e.to_string())?
+}
+
+#[derive(serde::Deserialize)]
+struct MergeInput {
+    path: String,
+    password: Option<Secret<String>>,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, serde::Deserialize)]
+enum MergeCollisionPolicy {
+    // Everything goes to the root; a file clashing with an earlier one gets " (2)", ...
+    #[default]
+    Rename,
+    // Each input goes into a folder named after its archive, so only those can clash
+    Prefix,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MergeSummary {
+    message: String,
+    // Files in the merged archive
+    entries: usize,
+    // "<original> -> <renamed>" for every clash that was resolved
+    collisions: Vec<String>,
+}
+
+// Moves `src` to `dest_dir/name`, or to a free " (2)" name if that's taken. Folders that
+// both sides have are merged instead when `merge_dirs` is set.
+fn move_into(
+    src: &Path,
+    dest_dir: &Path,
+    name: &std::ffi::OsStr,
+    merge_dirs: bool,
+    merged_root: &Path,
+    collisions: &mut Vec<String>,
+) -> Result<(), String> {
+    let target = dest_dir.join(name);
+    if fs::symlink_metadata(&target).is_err() {
+        return fs::rename(src, &target).map_err(|e| e.to_string());
+    }
+    if merge_dirs && src.is_dir() && target.is_dir() {
+        let mut children = fs::read_dir(src)
+            .map_err(|e| e.to_string())?
+            .map(|entry| entry.map(|e| e.file_name()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        children.sort();
+        for child in children {
+            move_into(&src.join(&child), &target, &child, merge_dirs, merged_root, collisions)?;
+        }
+        return Ok(());
+    }
+
+    let renamed = unique_output_path(dest_dir, &name.to_string_lossy());
+    fs::rename(src, &renamed).map_err(|e| e.to_string())?;
+    let display = |path: &Path| path.strip_prefix(merged_root).unwrap_or(path).to_string_lossy().replace('\\', "/");
+    collisions.push(format!("{} -> {}", display(&target), display(&renamed)));
+    Ok(())
+}
+
+// Each input is extracted into its own temp folder, moved into a shared one and the
+// result archived like convert_archive does; everything goes through disk, not memory
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn merge_archives(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    inputs: Vec<MergeInput>,
+    output: String,
+    output_password: Secret<String>,
+    method: EncryptionMethod,
+    collision_policy: Option<MergeCollisionPolicy>,
+    job_id: Option<JobId>,
+) -> Result<MergeSummary, String> {
+    let job = state.jobs.start(JobKind::Decrypt, job_id)?;
+    let cancel_flag = job.cancel_flag();
+    let output_password = output_password.expose_secret().clone();
+    let collision_policy = collision_policy.unwrap_or_default();
+    if inputs.len() < 2 {
+        return Err("Select at least two archives to merge".to_string());
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let _job = job.enter();
+        let canonical_output = Path::new(&output).canonicalize().ok();
+        let temp_dir = app_temp_dir().map_err(|e| e.to_string())?;
+        let merged = temp_dir.path().join("merged");
+        fs::create_dir(&merged).map_err(|e| e.to_string())?;
+        let mut collisions = Vec::new();
+
+        for (index, input) in inputs.iter().enumerate() {
+            let input_path = Path::new(&input.path);
+            if canonical_output.is_some() && canonical_output == input_path.canonicalize().ok() {
+                return Err("The merged archive can't replace one of its inputs".to_string());
+            }
+            if cancel_flag.load(Ordering::SeqCst) {
+                return Err("Merge cancelled by user.".to_string());
+            }
+            let stem = input_path.file_stem().and_then(|s| s.to_str()).unwrap_or("archive");
+            emit_status(&app_handle, format!("Extraction de {} ({}/{})...", stem, index + 1, inputs.len())).map_err(|e| e.to_string())?;
+
+            let staging = temp_dir.path().join(index.to_string());
+            fs::create_dir(&staging).map_err(|e| e.to_string())?;
+            let staging_str = staging.to_str().ok_or("Invalid path encoding")?;
+            let password = input.password.as_ref().map(|p| p.expose_secret().as_str()).unwrap_or_default();
+            let options = DecryptOptions::default();
+            extract_archive(&app_handle, &cancel_flag, input_path, staging_str, password, &options, &mut SkippedEntries::new(&options))
+                .map_err(|e| format!("{}: {}", input.path, e))?;
+
+            match collision_policy {
+                MergeCollisionPolicy::Prefix => {
+                    move_into(&staging, &merged, std::ffi::OsStr::new(stem), false, &merged, &mut collisions)?;
+                }
+                MergeCollisionPolicy::Rename => {
+                    move_into(&staging, temp_dir.path(), std::ffi::OsStr::new("merged"), true, &merged, &mut collisions)?;
+                }
+            }
+        }
+
+        let mut top_level = fs::read_dir(&merged)
+            .map_err(|e| e.to_string())?
+            .map(|entry| entry.map(|e| e.path().to_string_lossy().into_owned()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        if top_level.is_empty() {
+            return Err("The archives have no entries to merge".to_string());
+        }
+        top_level.sort();
+        let entries = WalkDir::new(&merged)
+            .min_depth(1)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|e| !e.file_type().is_dir())
+            .count();
+
+        let summary = encrypt_to_path(
+            &app_handle,
+            &cancel_flag,
+            &top_level,
+            &output,
+            &output_password,
+            &method,
+            &EncryptOptions::default(),
+        )?;
+        Ok(MergeSummary {
+            message: summary.message,
+            entries,
+            collisions,
+        })
+    }).await.map_err(|e| e.to_string())?
+}
+
+// New name of `name` when `old` is renamed to `new`; renaming a folder moves its content
+fn renamed_entry(name: &str, old: &str, new: &str) -> Option<String> {
+    if name == old {
+        return Some(new.to_string());
+    }
+    let rest = name.strip_prefix(old).filter(|_| old.ends_with('/'))?;
+    Some(format!("{}{}", new, rest))
+}
+
+// Relative, '/' separated, no `.`/`..` components and nothing a Windows path would treat
+// as a drive or stream
+fn validate_entry_name(name: &str) -> Result<(), String> {
+    let path = name.strip_suffix('/').unwrap_or(name);
+    let valid = !path.is_empty()
+        && !path.contains(['\\', ':', '\0'])
+        && path.split('/').all(|part| !part.is_empty() && part != "." && part != "..");
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid entry name: {:?}", name))
+    }
+}
+
+// Zip only. The zip crate can't copy an encrypted entry as is, so every entry is read and
+// written again, with the cipher, compression, times, permissions and extra fields it had;
+// the salts change, the content doesn't. The rewritten archive replaces the original only
+// once it's complete.
+#[tauri::command]
+async fn rename_entry(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    file_path: String,
+    old_name: String,
+    new_name: String,
+    password: Option<Secret<String>>,
+    job_id: Option<JobId>,
+) -> Result<String, String> {
+    let job = state.jobs.start(JobKind::Decrypt, job_id)?;
+    let cancel_flag = job.cancel_flag();
+    let password = password.map(|p| p.expose_secret().clone()).unwrap_or_default();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let _job = job.enter();
+        let path = Path::new(&file_path);
+        if is_7z_path(path) || is_rar_path(path) || is_container(path) {
+            return Err("Renaming entries is only supported for zip archives".to_string());
+        }
+        let mut archive = zip::ZipArchive::new(File::open(path).map_err(|e| e.to_string())?)
+            .map_err(|e| zip_error_message(path, e))?;
+        if read_name_manifest(&mut archive, path, &password)?.is_some() {
+            return Err("Entries of an archive with obfuscated names can't be renamed".to_string());
+        }
+        let duplicates = read_dedup_manifest(&mut archive, path, &password)?;
+
+        if archive.index_for_name(&old_name).is_none() {
+            return Err(format!("No entry named {}", old_name));
+        }
+        let new_name = new_name.replace('\\', "/");
+        // Folders keep their trailing slash, files can't gain one
+        let new_name = match (old_name.ends_with('/'), new_name.ends_with('/')) {
+            (true, false) => format!("{}/", new_name),
+            (false, true) => return Err(format!("{} is a file, its new name can't end with /", old_name)),
+            _ => new_name,
+        };
+        validate_entry_name(&new_name)?;
+        if new_name == old_name {
+            return Err("The new name is the same as the old one".to_string());
+        }
+
+        // Different case only would still clash when extracted on Windows or macOS, and a
+        // file can't share its name with a folder
+        let kept: Vec<String> = archive
+            .file_names()
+            .filter(|name| renamed_entry(name, &old_name, &new_name).is_none())
+            .map(str::to_lowercase)
+            .collect();
+        let clash = archive
+            .file_names()
+            .filter_map(|name| renamed_entry(name, &old_name, &new_name))
+            .find(|renamed| {
+                let renamed = renamed.to_lowercase();
+                let renamed = renamed.trim_end_matches('/');
+                kept.iter().any(|name| {
+                    name.trim_end_matches('/') == renamed || name.strip_prefix(renamed).is_some_and(|rest| rest.starts_with('/'))
+                })
+            });
+        if let Some(clash) = clash {
+            return Err(format!("An entry named {} already exists", clash));
+        }
+
+        let parent = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let temp = tempfile::Builder::new()
+            .prefix(".eazip-rename-")
+            .tempfile_in(parent)
+            .map_err(|e| format!("Failed to create a temp file next to the archive: {}", e))?;
+        let mut zip = ZipWriter::new(create_output(temp.path(), None).map_err(|e| e.to_string())?);
+        zip.set_raw_comment(archive.comment().into());
+
+        emit_status(&app_handle, "Renommage en cours...").map_err(|e| e.to_string())?;
+        let total_size = (0..archive.len())
+            .filter_map(|i| archive.by_index_raw(i).ok().map(|f| f.size()))
+            .fold(0u64, u64::saturating_add);
+        let mut progress = ProgressTracker::new(&app_handle, total_size);
+        let cancelled = "Rename cancelled by user.";
+        for i in 0..archive.len() {
+            if cancel_flag.load(Ordering::SeqCst) {
+                return Err(cancelled.to_string());
+            }
+            let mut file = archive.by_index_decrypt(i, password.as_bytes()).map_err(|e| zip_error_message(path, e))?;
+            let name = renamed_entry(file.name(), &old_name, &new_name).unwrap_or_else(|| file.name().to_string());
+
+            // Stored stays stored, anything this build can't write (bzip2, zstd...) becomes deflate
+            let method = match file.compression() {
+                CompressionMethod::Stored => CompressionMethod::Stored,
+                _ => CompressionMethod::Deflated,
+            };
+            let mut entry_options: FullFileOptions<'_> = FileOptions::default()
+                .compression_method(method)
+                .large_file(file.size().max(file.compressed_size()) > ZIP64_SIZE_THRESHOLD)
+                .last_modified_time(file.last_modified().unwrap_or_else(zip::DateTime::default_for_write));
+            if let Some(mode) = file.unix_mode() {
+                entry_options = entry_options.unix_permissions(mode);
+            }
+            // The writer adds its own Zip64 and AES fields, add_unicode_path_fields the Unicode path
+            let mut extra = file.extra_data().unwrap_or_default();
+            while extra.len() >= 4 {
+                let header_id = u16::from_le_bytes([extra[0], extra[1]]);
+                let len = u16::from_le_bytes([extra[2], extra[3]]) as usize;
+                let Some(data) = extra.get(4..4 + len) else { break };
+                if ![0x0001, 0x9901, 0x7075].contains(&header_id) {
+                    let _ = entry_options.add_extra_data(header_id, data.into(), false);
+                }
+                extra = &extra[4 + len..];
+            }
+
+            let written = if file.is_dir() {
+                zip.add_directory(name, entry_options)
+            } else {
+                let entry_options = match zip_entry_encryption(&file).cipher {
+                    EntryCipher::Aes128 => entry_options.with_aes_encryption(AesMode::Aes128, &password),
+                    EntryCipher::Aes192 => entry_options.with_aes_encryption(AesMode::Aes192, &password),
+                    EntryCipher::Aes256 => entry_options.with_aes_encryption(AesMode::Aes256, &password),
+                    EntryCipher::ZipCrypto => entry_options.with_deprecated_encryption(password.as_bytes()),
+                    _ => entry_options,
+                };
+                if file.is_symlink() {
+                    let mut target = String::new();
+                    file.read_to_string(&mut target).map_err(|e| e.to_string())?;
+                    zip.add_symlink(name, target, entry_options)
+                } else if let Some(duplicates) = duplicates.as_ref().filter(|_| file.name() == DEDUP_MANIFEST_ENTRY) {
+                    // The duplicates manifest names entries, it follows the rename
+                    let renamed: DedupManifest = duplicates
+                        .iter()
+                        .map(|(copy, reference)| {
+                            let rename = |name: &str| renamed_entry(name, &old_name, &new_name).unwrap_or_else(|| name.to_string());
+                            (rename(copy), DedupReference { source: rename(&reference.source), mtime: reference.mtime })
+                        })
+                        .collect();
+                    let data = serde_json::to_vec(&renamed).map_err(|e| e.to_string())?;
+                    zip.start_file(name, entry_options).and_then(|_| zip.write_all(&data).map_err(Into::into))
+                } else {
+                    zip.start_file(name, entry_options).map_err(|e| e.to_string())?;
+                    copy_with_progress(&mut file, &mut zip, &cancel_flag, &mut progress, cancelled)?;
+                    Ok(())
+                }
+            };
+            written.map_err(|e| format!("Failed to write to zip: {}", e))?;
+            progress.finish_entry();
+        }
+
+        emit_status(&app_handle, "Finalisation de l'archive...").map_err(|e| e.to_string())?;
+        let file = zip.finish().map_err(|e| format!("Failed to finish zip: {}", e))?;
+        finish_output(file).map_err(|e| format!("Failed to write {}: {}", temp.path().display(), e))?;
+        add_unicode_path_fields(temp.path()).map_err(|e| format!("Failed to write {}: {}", temp.path().display(), e))?;
+        drop(archive);
+        if let Ok(metadata) = fs::metadata(path) {
+            let _ = fs::set_permissions(temp.path(), metadata.permissions());
+        }
+        temp.persist(path).map_err(|e| format!("Failed to replace {}: {}", file_path, e.error))?;
+
+        emit_progress(&app_handle, 100).map_err(|e| e.to_string())?;
+        emit_status(&app_handle, "Terminé !").map_err(|e| e.to_string())?;
+        Ok(format!("{} renamed to {}", old_name, new_name))
+    }).await.map_err(|e| e.to_string())?
+}
+
+fn read_archive_entries(path: &Path, password: &str) -> Result<Vec<ArchiveEntry>, String> {
+    if is_7z_path(path) {
+        // 7z headers are usually encrypted, so the password is needed to list them
+        let archive = sevenz_rust2::Archive::open_with_password(path, &password.into())
+            .map_err(|e| e.to_string())?;
+        return Ok(archive
+            .files
+            .iter()
+            .map(|f| ArchiveEntry {
+                name: f.name().to_string(),
+                is_dir: f.is_directory(),
+                size: f.size(),
+                compressed_size: f.compressed_size,
+                crc32: f.crc as u32,
+                encrypted: !password.is_empty(),
+                error: None,
+                modified: f.has_last_modified_date.then(|| unix_secs(f.last_modified_date().into())).flatten(),
+            })
+            .collect());
+    }
+
+    if is_rar_path(path) {
+        let listing = unrar::Archive::with_password(path, password)
+            .open_for_listing()
+            .map_err(rar_error)?;
+        let mut entries = Vec::new();
+        for header in listing {
+            match header {
+                Ok(h) => entries.push(ArchiveEntry {
+                    name: h.filename.to_string_lossy().to_string(),
+                    is_dir: h.is_directory(),
+                    size: h.unpacked_size,
+                    compressed_size: 0,
+                    crc32: h.file_crc,
+                    encrypted: h.is_encrypted(),
+                    error: None,
+                    // DOS timestamp, date in the high half
+                    modified: zip::DateTime::try_from_msdos((h.file_time >> 16) as u16, h.file_time as u16)
+                        .ok()
+                        .and_then(system_time_from_zip_datetime)
+                        .and_then(unix_secs),
+                }),
+                // The listing iterator stops after the first damaged header
+                Err(e) => entries.push(ArchiveEntry::damaged(format!("#{}", entries.len()), rar_error(e))),
+            }
+        }
+        return Ok(entries);
+    }
+
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mut archive = match zip::ZipArchive::new(file) {
+        Ok(archive) => archive,
+        Err(e) => {
+            // Without a readable central directory (e.g. truncated download),
+            // fall back to whatever the local headers still describe
+            let mut file = File::open(path).map_err(|e| e.to_string())?;
+            let scanned = scan_local_headers(&mut file).map_err(|e| e.to_string())?;
+            if scanned.is_empty() {
+                return Err(e.to_string());
+            }
+            return Ok(scanned
+                .into_iter()
+                .map(|s| ArchiveEntry {
+                    is_dir: s.name.ends_with('/'),
+                    name: s.name,
+                    size: s.size,
+                    compressed_size: s.compressed_size,
+                    crc32: s.crc32,
+                    encrypted: s.flags & 0x0001 != 0,
+                    error: s.error,
+                    modified: None,
+                })
+                .collect());
+        }
+    };
+
+    // A damaged entry only marks its own row, the rest of the listing stays usable
+    let mut entries = Vec::with_capacity(archive.len());
+    for i in 0..archive.len() {
+        let name = archive
+            .name_for_index(i)
+            .map(|n| n.to_string())
+            .unwrap_or_else(|| format!("#{}", i));
+
+        let entry = match archive.by_index_raw(i) {
+            Ok(f) => ArchiveEntry {
+                name,
+                is_dir: f.is_dir(),
+                size: f.size(),
+                compressed_size: f.compressed_size(),
+                crc32: f.crc32(),
+                encrypted: f.encrypted(),
+                error: None,
+                modified: zip_entry_mtime(&f).and_then(unix_secs),
+            },
+            Err(e) => ArchiveEntry::damaged(name, e.to_string()),
+        };
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
+#[tauri::command]
+async fn list_archive(
+    file_path: String,
+    password: Option<Secret<String>>,
+) -> Result<Vec<ArchiveEntry>, String> {
+    let password = password.map(|p| p.expose_secret().clone()).unwrap_or_default();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        read_archive_entries(Path::new(&file_path), &password)
+    }).await.map_err(|e| e.to_string())?
+}
+
+#[derive(Clone, Copy, PartialEq, serde::Serialize)]
+enum EntryCipher {
+    None,
+    ZipCrypto,
+    Aes128,
+    Aes192,
+    Aes256,
+    SevenZipAes256,
+    // RAR doesn't say which of AES-128 (RAR 3) or AES-256 (RAR 5) without the password
+    Rar,
+    // Entries use different ciphers, see EncryptionInfo::entries
+    Mixed,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EntryEncryption {
+    name: String,
+    cipher: EntryCipher,
+    // WinZip AES vendor version: 1 (AE-1, keeps the CRC) or 2 (AE-2)
+    aes_version: Option<u16>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EncryptionInfo {
+    format: &'static str,
+    cipher: EntryCipher,
+    // 7z: names are encrypted too, nothing is listed without the password
+    header_encrypted: bool,
+    // Per-entry detail, only when the archive is mixed
+    entries: Vec<EntryEncryption>,
+}
+
+// Cipher and strength from the zip AES extra field (0x9901): vendor version, "AE",
+// strength 1-3, actual compression method
+fn zip_entry_encryption(file: &zip::read::ZipFile<'_>) -> EntryEncryption {
+    let aes = zip_extra_field(file, 0x9901).filter(|data| data.len() >= 7 && &data[2..4] == b"AE");
+    let (cipher, aes_version) = match aes {
+        _ if !file.encrypted() => (EntryCipher::None, None),
+        None => (EntryCipher::ZipCrypto, None),
+        Some(data) => {
+            let cipher = match data[4] {
+                1 => EntryCipher::Aes128,
+                2 => EntryCipher::Aes192,
+                _ => EntryCipher::Aes256,
+            };
+            (cipher, Some(u16::from_le_bytes([data[0], data[1]])))
+        }
+    };
+    EntryEncryption {
+        name: file.name().to_string(),
+        cipher,
+        aes_version,
+    }
+}
+
+// Directories are left out, zip writers usually store them unencrypted
+fn read_entry_encryption(path: &Path) -> Result<(Vec<EntryEncryption>, bool), String> {
+    if is_7z_path(path) {
+        let archive = match sevenz_rust2::Archive::open_with_password(path, &"".into()) {
+            Ok(archive) => archive,
+            Err(sevenz_rust2::Error::PasswordRequired | sevenz_rust2::Error::MaybeBadPassword(_)) => {
+                return Ok((Vec::new(), true));
+            }
+            Err(e) => return Err(e.to_string()),
+        };
+        let entries = archive
+            .files
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| !f.is_directory())
+            .filter_map(|(i, f)| {
+                let block = archive.blocks.get(archive.stream_map.file_block_index.get(i).copied().flatten()?)?;
+                let encrypted = block
+                    .coders
+                    .iter()
+                    .any(|c| c.encoder_method_id() == sevenz_rust2::EncoderMethod::ID_AES256SHA256);
+                Some(EntryEncryption {
+                    name: f.name().to_string(),
+                    cipher: if encrypted { EntryCipher::SevenZipAes256 } else { EntryCipher::None },
+                    aes_version: None,
+                })
+            })
+            .collect();
+        return Ok((entries, false));
+    }
+
+    if is_rar_path(path) {
+        let listing = unrar::Archive::new(path).open_for_listing().map_err(rar_error)?;
+        let mut entries = Vec::new();
+        for header in listing {
+            let header = header.map_err(rar_error)?;
+            if !header.is_directory() {
+                entries.push(EntryEncryption {
+                    name: header.filename.to_string_lossy().to_string(),
+                    cipher: if header.is_encrypted() { EntryCipher::Rar } else { EntryCipher::None },
+                    aes_version: None,
+                });
+            }
+        }
+        return Ok((entries, false));
+    }
+
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| zip_error_message(path, e))?;
+    let mut entries = Vec::new();
+    for i in 0..archive.len() {
+        let file = archive.by_index_raw(i).map_err(|e| zip_error_message(path, e))?;
+        if !file.is_dir() {
+            entries.push(zip_entry_encryption(&file));
+        }
+    }
+    Ok((entries, false))
+}
+
+// Read from the headers alone, no password needed
+#[tauri::command]
+async fn get_encryption_info(file_path: String) -> Result<EncryptionInfo, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = Path::new(&file_path);
+        let (entries, header_encrypted) = read_entry_encryption(path)?;
+        let cipher = if header_encrypted {
+            EntryCipher::SevenZipAes256
+        } else {
+            let mut ciphers = entries.iter().map(|e| (e.cipher, e.aes_version));
+            match ciphers.next() {
+                None => EntryCipher::None,
+                Some(first) if ciphers.all(|c| c == first) => first.0,
+                Some(_) => EntryCipher::Mixed,
+            }
+        };
+        Ok(EncryptionInfo {
+            format: archive_format(path),
+            cipher,
+            header_encrypted,
+            entries: if cipher == EntryCipher::Mixed { entries } else { Vec::new() },
+        })
+    }).await.map_err(|e| e.to_string())?
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArchiveInspection {
+    total_uncompressed: u64,
+    file_count: usize,
+    dir_count: usize,
+}
+
+// Totals from the same header listing as list_archive, no entry data is read
+#[tauri::command]
+async fn inspect_archive(
+    file_path: String,
+    password: Option<Secret<String>>,
+) -> Result<ArchiveInspection, String> {
+    let password = password.map(|p| p.expose_secret().clone()).unwrap_or_default();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let entries = read_archive_entries(Path::new(&file_path), &password)?;
+        let dir_count = entries.iter().filter(|e| e.is_dir).count();
+        Ok(ArchiveInspection {
+            total_uncompressed: entries.iter().fold(0u64, |acc, e| acc.saturating_add(e.size)),
+            file_count: entries.len() - dir_count,
+            dir_count,
+        })
+    }).await.map_err(|e| e.to_string())?
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArchiveHealth {
+    valid: bool,
+    entry_count: usize,
+    total_size: u64,
+    has_encryption: bool,
+    format: &'static str,
+    // Entries with unreadable metadata, plus those failing the full test when it ran
+    bad_entries: Vec<String>,
+}
+
+fn archive_format(path: &Path) -> &'static str {
+    if is_7z_path(path) {
+        "7z"
+    } else if is_rar_path(path) {
+        "rar"
+    } else {
+        "zip"
+    }
+}
+
+// Decodes every entry into a sink so CRC mismatches surface. 7z and RAR stop at the
+// first failure (solid blocks can't be resumed), zip goes on with the next entry.
+fn test_archive_entries(path: &Path, password: &str) -> Result<Vec<String>, String> {
+    let mut bad_entries = Vec::new();
+
+    if is_7z_path(path) {
+        let mut reader = sevenz_rust2::ArchiveReader::open(path, password.into()).map_err(|e| e.to_string())?;
+        let mut current = None;
+        let result = reader.for_each_entries(|entry, entry_reader| {
+            current = Some(entry.name().to_string());
+            std::io::copy(entry_reader, &mut std::io::sink())?;
+            Ok(true)
+        });
+        if let Err(e) = result {
+            log::warn!("Archive test failed: {}", e);
+            bad_entries.push(current.unwrap_or_else(|| e.to_string()));
+        }
+    } else if is_rar_path(path) {
+        let mut archive = unrar::Archive::with_password(path, password)
+            .open_for_processing()
+            .map_err(rar_error)?;
+        while let Some(header) = archive.read_header().map_err(rar_error)? {
+            let name = header.entry().filename.to_string_lossy().to_string();
+            archive = match header.test() {
+                Ok(archive) => archive,
+                Err(e) => {
+                    log::warn!("Archive test failed on {}: {}", name, rar_error(e));
+                    bad_entries.push(name);
+                    break;
+                }
+            };
+        }
+    } else {
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        let mut archive = zip::ZipArchive::new(file).map_err(|e| zip_error_message(path, e))?;
+        for i in 0..archive.len() {
+            let name = archive.name_for_index(i).map(|n| n.to_string()).unwrap_or_else(|| format!("#{}", i));
+            let tested = archive
+                .by_index_decrypt(i, password.as_bytes())
+                .map_err(|e| e.to_string())
+                .and_then(|mut f| std::io::copy(&mut f, &mut std::io::sink()).map_err(|e| e.to_string()));
+            if let Err(e) = tested {
+                log::warn!("Archive test failed on {}: {}", name, e);
+                bad_entries.push(name);
+            }
+        }
+    }
+
+    Ok(bad_entries)
+}
+
+// One call for a details panel: listing totals, plus a full CRC test with `full_test`
+#[tauri::command]
+async fn archive_health(
+    file_path: String,
+    password: Option<Secret<String>>,
+    full_test: bool,
+) -> Result<ArchiveHealth, String> {
+    let password = password.map(|p| p.expose_secret().clone()).unwrap_or_default();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = Path::new(&file_path);
+        let entries = read_archive_entries(path, &password)?;
+        let mut bad_entries: Vec<String> = entries.iter().filter(|e| e.error.is_some()).map(|e| e.name.clone()).collect();
+        if full_test {
+            for name in test_archive_entries(path, &password)? {
+                if !bad_entries.contains(&name) {
+                    bad_entries.push(name);
+                }
+            }
+        }
+
+        Ok(ArchiveHealth {
+            valid: bad_entries.is_empty(),
+            entry_count: entries.len(),
+            total_size: entries.iter().fold(0u64, |acc, e| acc.saturating_add(e.size)),
+            has_encryption: entries.iter().any(|e| e.encrypted),
+            format: archive_format(path),
+            bad_entries,
+        })
+    }).await.map_err(|e| e.to_string())?
+}
+
+// Wildcard patterns follow the plaintext_patterns rules, anything else is a
+// substring search. Both ignore case, this backs a search box.
+#[tauri::command]
+async fn search_archive(
+    file_path: String,
+    pattern: String,
+    password: Option<Secret<String>>,
+) -> Result<Vec<ArchiveEntry>, String> {
+    let password = password.map(|p| p.expose_secret().clone()).unwrap_or_default();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let pattern = pattern.to_lowercase();
+        let is_glob = pattern.contains(['*', '?']);
+        let entries = read_archive_entries(Path::new(&file_path), &password)?;
+        Ok(entries
+            .into_iter()
+            .filter(|entry| {
+                let name = entry.name.to_lowercase();
+                if is_glob {
+                    matches_any_pattern(std::slice::from_ref(&pattern), name.trim_end_matches('/'))
+                } else {
+                    name.contains(&pattern)
+                }
+            })
+            .collect())
+    }).await.map_err(|e| e.to_string())?
+}
+
+// Biggest files first by uncompressed size, folders left out. Only the top n are
+// sorted, the rest of the listing is just partitioned away.
+#[tauri::command]
+async fn largest_entries(
+    file_path: String,
+    n: usize,
+    password: Option<Secret<String>>,
+) -> Result<Vec<ArchiveEntry>, String> {
+    let password = password.map(|p| p.expose_secret().clone()).unwrap_or_default();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut entries: Vec<ArchiveEntry> = read_archive_entries(Path::new(&file_path), &password)?
+            .into_iter()
+            .filter(|entry| !entry.is_dir)
+            .collect();
+        let by_size = |a: &ArchiveEntry, b: &ArchiveEntry| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name));
+        if n < entries.len() {
+            entries.select_nth_unstable_by(n, by_size);
+            entries.truncate(n);
+        }
+        entries.sort_unstable_by(by_size);
+        Ok(entries)
+    }).await.map_err(|e| e.to_string())?
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArchiveDiff {
+    added: Vec<String>,
+    removed: Vec<String>,
+    changed: Vec<String>,
+}
+
+#[tauri::command]
+async fn diff_archives(
+    a: String,
+    b: String,
+    password_a: Option<Secret<String>>,
+    password_b: Option<Secret<String>>,
+) -> Result<ArchiveDiff, String> {
+    let password_a = password_a.map(|p| p.expose_secret().clone()).unwrap_or_default();
+    let password_b = password_b.map(|p| p.expose_secret().clone()).unwrap_or_default();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        // Metadata only, nothing gets extracted
+        let index = |entries: Vec<ArchiveEntry>| -> std::collections::BTreeMap<String, ArchiveEntry> {
+            entries
+                .into_iter()
+                .filter(|e| e.error.is_none())
+                .map(|e| (e.name.clone(), e))
+                .collect()
+        };
+        let old = index(read_archive_entries(Path::new(&a), &password_a)?);
+        let new = index(read_archive_entries(Path::new(&b), &password_b)?);
+
+        let mut diff = ArchiveDiff {
+            added: Vec::new(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+        };
+        for (name, entry) in &new {
+            match old.get(name) {
+                None => diff.added.push(name.clone()),
+                Some(old_entry) if entries_differ(old_entry, entry) => diff.changed.push(name.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.removed = old.keys().filter(|name| !new.contains_key(*name)).cloned().collect();
+
+        Ok(diff)
+    }).await.map_err(|e| e.to_string())?
+}
+
+// Size and CRC, except for small WinZip AES entries (AE-2) whose CRC is stored as 0:
+// those fall back to size and modification time
+fn entries_differ(old: &ArchiveEntry, new: &ArchiveEntry) -> bool {
+    if old.size != new.size {
+        return true;
+    }
+    if old.size > 0 && (old.crc32 == 0 || new.crc32 == 0) {
+        return match (old.modified, new.modified) {
+            (Some(old_time), Some(new_time)) => old_time.abs_diff(new_time) > MTIME_TOLERANCE_SECS,
+            // Nothing left to compare on, report it rather than miss a change
+            _ => true,
+        };
+    }
+    old.crc32 != new.crc32
+}
+
+#[derive(Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DirComparison {
+    // On disk but not in the archive
+    added: Vec<String>,
+    // In the archive but no longer on disk
+    removed: Vec<String>,
+    modified: Vec<String>,
+}
+
+// DOS timestamps only have a 2-second resolution
+const MTIME_TOLERANCE_SECS: u64 = 2;
+
+// The folder is walked exactly as encrypt_files would archive it with the same options,
+// so names line up with the entries. Only metadata is read from the archive; check_crc
+// also hashes files whose size and mtime look unchanged.
+#[tauri::command]
+async fn compare_archive_to_dir(
+    app_handle: tauri::AppHandle,
+    file_path: String,
+    dir: String,
+    password: Option<Secret<String>>,
+    options: Option<EncryptOptions>,
+    check_crc: bool,
+) -> Result<DirComparison, String> {
+    let password = password.map(|p| p.expose_secret().clone()).unwrap_or_default();
+    let options = options.unwrap_or_default();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let archive_path = Path::new(&file_path);
+        let mut archived: std::collections::BTreeMap<String, ArchiveEntry> = read_archive_entries(archive_path, &password)?
+            .into_iter()
+            .filter(|e| e.error.is_none())
+            .map(|e| (e.name.trim_end_matches('/').to_string(), e))
+            .collect();
+        let canonical_archive = archive_path.canonicalize().map_err(|e| e.to_string())?;
+        let (entries, ..) = collect_entries(&app_handle, std::slice::from_ref(&dir), &canonical_archive, &options, &IoRetry::new(&options))?;
+
+        // Small WinZip AES entries (AE-2) store 0 instead of the CRC, those get decrypted to find it
+        let mut zip_archive = None;
+        let mut stored_crc32 = |name: &str, stored: &ArchiveEntry| -> Result<u32, String> {
+            if stored.crc32 != 0 || stored.size == 0 || is_7z_path(archive_path) || is_rar_path(archive_path) {
+                return Ok(stored.crc32);
+            }
+            let archive = match &mut zip_archive {
+                Some(archive) => archive,
+                None => {
+                    let file = File::open(archive_path).map_err(|e| e.to_string())?;
+                    zip_archive.insert(zip::ZipArchive::new(file).map_err(|e| zip_error_message(archive_path, e))?)
+                }
+            };
+            let mut file = archive
+                .by_name_decrypt(name, password.as_bytes())
+                .map_err(|e| zip_error_message(archive_path, e))?;
+            reader_crc32(&mut file).map_err(|e| e.to_string())
+        };
+
+        let mut comparison = DirComparison::default();
+        for entry in &entries {
+            let name = manifest_key(&entry.rel_path);
+            let Some(stored) = archived.remove(&name) else {
+                comparison.added.push(name);
+                continue;
+            };
+            if entry.is_dir || stored.is_dir {
+                continue;
+            }
+            let mtime = fs::symlink_metadata(&entry.abs_path).ok().and_then(|m| m.modified().ok()).and_then(unix_secs);
+            let mtime_changed = match (stored.modified, mtime) {
+                (Some(stored), Some(current)) => stored.abs_diff(current) > MTIME_TOLERANCE_SECS,
+                _ => false,
+            };
+            let changed = stored.size != entry.size
+                || mtime_changed
+                || (check_crc
+                    && file_crc32(&entry.abs_path)
+                        .map_err(|e| format!("Failed to read {}: {}", entry.abs_path.display(), e))?
+                        != stored_crc32(&name, &stored)?);
+            if changed {
+                comparison.modified.push(name);
+            }
+        }
+        comparison.removed = archived.into_keys().collect();
+        comparison.added.sort();
+        comparison.modified.sort();
+
+        Ok(comparison)
+    }).await.map_err(|e| e.to_string())?
+}
+
+// Streams one decrypted entry into any writer, used for pipes/stdout rather than files
+fn stream_entry<W: Write, R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    cancel_flag: &AtomicBool,
+    path: &Path,
+    entry_name: &str,
+    password: &str,
+    writer: &mut W,
+) -> Result<u64, String> {
+    const CANCELLED: &str = "Extraction cancelled by user.";
+
+    if is_7z_path(path) {
+        let mut reader = sevenz_rust2::ArchiveReader::open(path, password.into())
+            .map_err(|e| e.to_string())?;
+        let size = reader
+            .archive()
+            .files
+            .iter()
+            .find(|f| f.name() == entry_name && !f.is_directory())
+            .map(|f| f.size())
+            .ok_or_else(|| format!("Entry not found: {}", entry_name))?;
+
+        let mut progress = ProgressTracker::new(app_handle, size);
+        let mut result: Option<Result<u64, String>> = None;
+        let res = reader.for_each_entries(|entry, entry_reader| {
+            if entry.name() != entry_name {
+                // Solid blocks have to be decoded in order, skip by draining
+                std::io::copy(entry_reader, &mut std::io::sink())
+                    .map_err(|e| sevenz_rust2::Error::Io(e, "Skip entry".into()))?;
+                return Ok(true);
+            }
+            let copied = copy_with_progress(entry_reader, writer, cancel_flag, &mut progress, CANCELLED);
+            let failed = copied.is_err();
+            result = Some(copied);
+            if failed {
+                Err(sevenz_rust2::Error::Other("Extraction aborted".into()))
+            } else {
+                // Stop at the entry, whatever follows is irrelevant
+                Err(sevenz_rust2::Error::Other("Entry streamed".into()))
+            }
+        });
+        return match result {
+            Some(r) => r,
+            None => res.map(|_| 0).map_err(|e| e.to_string()),
+        };
+    }
+
+    if is_rar_path(path) {
+        return Err("Streaming is not supported for RAR archives".to_string());
+    }
+
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| zip_error_message(path, e))?;
+    let mut entry = archive
+        .by_name_decrypt(entry_name, password.as_bytes())
+        .map_err(|e| match e {
+            zip::result::ZipError::FileNotFound => format!("Entry not found: {}", entry_name),
+            _ => zip_error_message(path, e),
+        })?;
+
+    let mut progress = ProgressTracker::new(app_handle, entry.size());
+    copy_with_progress(&mut entry, writer, cancel_flag, &mut progress, CANCELLED)
+}
+
+// `destination` is a named pipe (or any writable path), or "-" for the process stdout.
+// Writes block while the consumer is slow, which gives natural backpressure.
+#[tauri::command]
+async fn extract_entry_to_stream(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    file_path: String,
+    entry_name: String,
+    destination: String,
+    password: Option<Secret<String>>,
+    job_id: Option<JobId>,
+) -> Result<u64, String> {
+    let job = state.jobs.start(JobKind::Decrypt, job_id)?;
+    let cancel_flag = job.cancel_flag();
+    let password = password.map(|p| p.expose_secret().clone()).unwrap_or_default();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let _job = job.enter();
+        let path = Path::new(&file_path);
+
+        let written = if destination == "-" {
+            let stdout = std::io::stdout();
+            let mut out = stdout.lock();
+            let written = stream_entry(&app_handle, &cancel_flag, path, &entry_name, &password, &mut out)?;
+            out.flush().map_err(|e| e.to_string())?;
+            written
+        } else {
+            let mut out = fs::OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(&destination)
+                .map_err(|e| format!("Failed to open destination: {}", e))?;
+            stream_entry(&app_handle, &cancel_flag, path, &entry_name, &password, &mut out)?
+        };
+
+        Ok(written)
+    }).await.map_err(|e| e.to_string())?
+}
+
+const THUMBNAIL_MAX_INPUT: usize = 50 * 1024 * 1024; // 50 MB
+const THUMBNAIL_MAX_DIM: u32 = 1024;
+// Decoded pixels, so a tiny but huge-dimension image can't exhaust memory
+const THUMBNAIL_MAX_SOURCE_DIM: u32 = 16_384;
+
+// In-memory sink for stream_entry that gives up past `limit` bytes, keeping what fit
+struct CappedBuffer {
+    data: Vec<u8>,
+    limit: usize,
+    truncated: bool,
+}
+
+impl CappedBuffer {
+    fn new(limit: usize) -> Self {
+        CappedBuffer { data: Vec::new(), limit, truncated: false }
+    }
+}
+
+impl Write for CappedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.data.len() + buf.len() > self.limit {
+            let room = self.limit - self.data.len();
+            self.data.extend_from_slice(&buf[..room]);
+            self.truncated = true;
+            return Err(std::io::Error::other(format!(
+                "Entry too large for a preview (limit: {} bytes)",
+                self.limit
+            )));
+        }
+        self.data.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+// PNG preview of an image entry, decoded in memory without extracting anything
+#[tauri::command]
+async fn get_entry_thumbnail(
+    app_handle: tauri::AppHandle,
+    file_path: String,
+    entry_name: String,
+    password: Option<Secret<String>>,
+    max_dim: u32,
+) -> Result<Vec<u8>, String> {
+    let password = password.map(|p| p.expose_secret().clone()).unwrap_or_default();
+    let max_dim = max_dim.clamp(1, THUMBNAIL_MAX_DIM);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut buffer = CappedBuffer::new(THUMBNAIL_MAX_INPUT);
+        // Not tied to either cancel flag, a preview is short and shouldn't stop a running job
+        stream_entry(&app_handle, &AtomicBool::new(false), Path::new(&file_path), &entry_name, &password, &mut buffer)?;
+
+        let mut reader = image::ImageReader::new(std::io::Cursor::new(buffer.data))
+            .with_guessed_format()
+            .map_err(|e| e.to_string())?;
+        let mut limits = image::Limits::default();
+        limits.max_image_width = Some(THUMBNAIL_MAX_SOURCE_DIM);
+        limits.max_image_height = Some(THUMBNAIL_MAX_SOURCE_DIM);
+        reader.limits(limits);
+        let image = reader.decode().map_err(|e| format!("Not a supported image: {}", e))?;
+
+        let mut png = std::io::Cursor::new(Vec::new());
+        image
+            .thumbnail(max_dim, max_dim)
+            .write_to(&mut png, image::ImageFormat::Png)
+            .map_err(|e| e.to_string())?;
+        Ok(png.into_inner())
+    }).await.map_err(|e| e.to_string())?
+}
+
+const TEXT_PREVIEW_MAX_BYTES: usize = 4 * 1024 * 1024;
+// NUL bytes this early mean a binary file, unless a BOM says UTF-16
+const BINARY_SNIFF_LEN: usize = 8 * 1024;
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TextPreview {
+    text: String,
+    encoding: &'static str,
+    truncated: bool,
+}
+
+// Reading stops at max_bytes (capped at 4 MB), so a huge entry is never fully decrypted.
+// The encoding comes from the BOM, else from chardetng's guess.
+#[tauri::command]
+async fn preview_text_entry(
+    app_handle: tauri::AppHandle,
+    file_path: String,
+    entry_name: String,
+    password: Option<Secret<String>>,
+    max_bytes: usize,
+) -> Result<TextPreview, String> {
+    let password = password.map(|p| p.expose_secret().clone()).unwrap_or_default();
+    let max_bytes = max_bytes.clamp(1, TEXT_PREVIEW_MAX_BYTES);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut buffer = CappedBuffer::new(max_bytes);
+        // Not tied to either cancel flag, like get_entry_thumbnail
+        let streamed = stream_entry(&app_handle, &AtomicBool::new(false), Path::new(&file_path), &entry_name, &password, &mut buffer);
+        if let Err(e) = streamed {
+            if !buffer.truncated {
+                return Err(e);
+            }
+        }
+
+        let data = &buffer.data;
+        let encoding = match encoding_rs::Encoding::for_bom(data) {
+            Some((encoding, _)) => encoding,
+            None => {
+                if data[..data.len().min(BINARY_SNIFF_LEN)].contains(&0) {
+                    return Err("This entry looks like a binary file".to_string());
+                }
+                let mut detector = chardetng::EncodingDetector::new();
+                detector.feed(data, !buffer.truncated);
+                detector.guess(None, true)
+            }
+        };
+        // A cut-off entry may end mid-character, the decoder holds those bytes back
+        // instead of turning them into a replacement character
+        let mut decoder = encoding.new_decoder();
+        let mut text = String::with_capacity(decoder.max_utf8_buffer_length(data.len()).unwrap_or(data.len()));
+        let _ = decoder.decode_to_string(data, &mut text, !buffer.truncated);
+
+        Ok(TextPreview {
+            text,
+            encoding: encoding.name(),
+            truncated: buffer.truncated,
+        })
+    }).await.map_err(|e| e.to_string())?
+}
+
+const RANGE_READ_MAX_BYTES: u64 = 16 * 1024 * 1024;
+
+// Drops everything before `skip`, keeps `length` bytes, then stops the stream with an
+// error once the range is complete
+struct RangeBuffer {
+    data: Vec<u8>,
+    skip: u64,
+    length: usize,
+    stopped: bool,
+}
+
+impl Write for RangeBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.data.len() == self.length {
+            self.stopped = true;
+            return Err(std::io::Error::other("Range read"));
+        }
+        let skipped = (self.skip.min(buf.len() as u64)) as usize;
+        self.skip -= skipped as u64;
+        let kept = (buf.len() - skipped).min(self.length - self.data.len());
+        self.data.extend_from_slice(&buf[skipped..skipped + kept]);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+// Unencrypted Stored zip entries are read by seeking straight to the range. Anything
+// else is decoded from the start and dropped up to `offset`, so late ranges of big
+// compressed entries cost as much as reading that far. Ranges past the end come back
+// short or empty; `length` is capped at 16 MB.
+#[tauri::command]
+async fn read_entry_range(
+    app_handle: tauri::AppHandle,
+    file_path: String,
+    entry_name: String,
+    offset: u64,
+    length: u64,
+    password: Option<Secret<String>>,
+) -> Result<Vec<u8>, String> {
+    let password = password.map(|p| p.expose_secret().clone()).unwrap_or_default();
+    let length = length.min(RANGE_READ_MAX_BYTES);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = Path::new(&file_path);
+        if !is_7z_path(path) && !is_rar_path(path) {
+            let file = File::open(path).map_err(|e| e.to_string())?;
+            let mut archive = zip::ZipArchive::new(file).map_err(|e| zip_error_message(path, e))?;
+            let index = archive
+                .index_for_name(&entry_name)
+                .ok_or_else(|| format!("Entry not found: {}", entry_name))?;
+            let (stored, size) = {
+                let entry = archive.by_index_raw(index).map_err(|e| zip_error_message(path, e))?;
+                (entry.compression() == zip::CompressionMethod::Stored && !entry.encrypted(), entry.size())
+            };
+            if stored {
+                let mut entry = archive.by_index_seek(index).map_err(|e| zip_error_message(path, e))?;
+                entry.seek(SeekFrom::Start(offset.min(size))).map_err(|e| e.to_string())?;
+                let mut data = Vec::new();
+                entry.take(length.min(size.saturating_sub(offset))).read_to_end(&mut data).map_err(|e| e.to_string())?;
+                return Ok(data);
+            }
+        }
+
+        let mut buffer = RangeBuffer { data: Vec::new(), skip: offset, length: length as usize, stopped: false };
+        // Not tied to either cancel flag, like get_entry_thumbnail
+        let streamed = stream_entry(&app_handle, &AtomicBool::new(false), path, &entry_name, &password, &mut buffer);
+        if let Err(e) = streamed {
+            if !buffer.stopped {
+                return Err(e);
+            }
+        }
+        Ok(buffer.data)
+    }).await.map_err(|e| e.to_string())?
+}
+
+// Re-wraps one local entry (header + data) as a standalone zip with a fresh central
+// directory, so the zip crate can decrypt and decompress it as usual
+fn rebuild_single_entry_zip(source: &mut File, entry: &ScannedEntry) -> std::io::Result<tempfile::NamedTempFile> {
+    let mut local = vec![0u8; (entry.data_start - entry.header_offset) as usize];
+    source.seek(SeekFrom::Start(entry.header_offset))?;
+    source.read_exact(&mut local)?;
+
+    // Values recovered from a data descriptor move into the header, the descriptor is dropped
+    if entry.flags & 0x0008 != 0 {
+        local[6..8].copy_from_slice(&(entry.flags & !0x0008).to_le_bytes());
+        local[14..18].copy_from_slice(&entry.crc32.to_le_bytes());
+        local[18..22].copy_from_slice(&(entry.compressed_size as u32).to_le_bytes());
+        local[22..26].copy_from_slice(&(entry.size as u32).to_le_bytes());
+    }
+
+    let mut out = app_temp_file(".zip")?;
+    out.write_all(&local)?;
+    std::io::copy(&mut Read::by_ref(source).take(entry.compressed_size), &mut out)?;
+
+    // Central header shares the local layout from "version needed" to "extra length"
+    let mut central = Vec::with_capacity(46 + local.len() - 30);
+    central.extend_from_slice(b"PK\x01\x02");
+    central.extend_from_slice(&20u16.to_le_bytes());
+    central.extend_from_slice(&local[4..30]);
+    central.extend_from_slice(&[0u8; 14]); // comment length, disk, attributes, offset 0
+    central.extend_from_slice(&local[30..]);
+    out.write_all(&central)?;
+
+    let central_offset = local.len() as u64 + entry.compressed_size;
+    let mut end = Vec::with_capacity(22);
+    end.extend_from_slice(b"PK\x05\x06");
+    end.extend_from_slice(&[0u8; 4]);
+    end.extend_from_slice(&1u16.to_le_bytes());
+    end.extend_from_slice(&1u16.to_le_bytes());
+    end.extend_from_slice(&(central.len() as u32).to_le_bytes());
+    end.extend_from_slice(&(central_offset as u32).to_le_bytes());
+    end.extend_from_slice(&0u16.to_le_bytes());
+    out.write_all(&end)?;
+
+    out.seek(SeekFrom::Start(0))?;
+    Ok(out)
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LostEntry {
+    name: String,
+    reason: String,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SalvageReport {
+    recovered: Vec<String>,
+    lost: Vec<LostEntry>,
+}
+
+// For zips whose central directory is damaged or missing: entries are recovered from
+// their local headers, anything unreadable is reported instead of aborting the run
+#[tauri::command]
+async fn salvage_archive(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    file_path: String,
+    output_dir: String,
+    password: Option<Secret<String>>,
+    job_id: Option<JobId>,
+) -> Result<SalvageReport, String> {
+    let job = state.jobs.start(JobKind::Decrypt, job_id)?;
+    let cancel_flag = job.cancel_flag();
+    let password = password.map(|p| p.expose_secret().clone()).unwrap_or_default();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let _job = job.enter();
+
+        let output_dir = Path::new(&output_dir);
+        fs::create_dir_all(output_dir).map_err(|e| e.to_string())?;
+        let canonical_output_dir = output_dir.canonicalize().map_err(|e| e.to_string())?;
+
+        let mut source = File::open(&file_path).map_err(|e| e.to_string())?;
+        let scanned = scan_local_headers(&mut source).map_err(|e| e.to_string())?;
+        if scanned.is_empty() {
+            return Err("No zip entries found in file".to_string());
+        }
+        if scanned.len() > MAX_FILE_COUNT {
+            return Err(format!("Too many files in archive (limit: {})", MAX_FILE_COUNT));
+        }
+
+        let mut report = SalvageReport {
+            recovered: Vec::new(),
+            lost: Vec::new(),
+        };
+        let total = scanned.len();
+        let mut total_extracted_size: u64 = 0;
+
+        for (i, entry) in scanned.iter().enumerate() {
+            if cancel_flag.load(Ordering::SeqCst) {
+                return Err("Salvage cancelled by user.".to_string());
+            }
+
+            let result = (|| -> Result<(), String> {
+                if let Some(error) = &entry.error {
+                    return Err(error.clone());
+                }
+
+                // Zip Slip Protection
+                let outpath = safe_entry_output_path(output_dir, &entry.name)?;
+                if entry.name.ends_with('/') {
+                    return fs::create_dir_all(&outpath).map_err(|e| e.to_string());
+                }
+                if total_extracted_size + entry.size > MAX_TOTAL_SIZE {
+                    return Err(format!("Total extracted size exceeds limit (limit: {} bytes)", MAX_TOTAL_SIZE));
+                }
+                if let Some(p) = outpath.parent() {
+                    if !p.exists() {
+                        fs::create_dir_all(p).map_err(|e| e.to_string())?;
+                    }
+                    let canonical_parent = p.canonicalize().map_err(|e| e.to_string())?;
+                    if !canonical_parent.starts_with(&canonical_output_dir) {
+                        return Err("Invalid file path (Zip Slip attempt detected)".to_string());
+                    }
+                }
+
+                let rebuilt = rebuild_single_entry_zip(&mut source, entry).map_err(|e| e.to_string())?;
+                let mut archive = zip::ZipArchive::new(rebuilt).map_err(|e| e.to_string())?;
+                let mut file = archive
+                    .by_index_decrypt(0, password.as_bytes())
+                    .map_err(|e| match e {
+                        zip::result::ZipError::InvalidPassword => "Mot de passe incorrect".to_string(),
+                        _ => e.to_string(),
+                    })?;
+
+                // The zip reader checks the CRC at the end, a partial file is not kept.
+                // The local header may lie about the size, so the copy is capped too
+                let remaining = MAX_TOTAL_SIZE - total_extracted_size;
+                let mut outfile = File::create(&outpath).map_err(|e| e.to_string())?;
+                let copied = match std::io::copy(&mut (&mut file).take(remaining + 1), &mut outfile) {
+                    Ok(written) if written > remaining => {
+                        Err(format!("Total extracted size exceeds limit (limit: {} bytes)", MAX_TOTAL_SIZE))
+                    }
+                    Ok(written) => Ok(written),
+                    Err(e) => Err(e.to_string()),
+                };
+                let written = match copied {
+                    Ok(written) => written,
+                    Err(e) => {
+                        drop(outfile);
+                        let _ = fs::remove_file(&outpath);
+                        return Err(e);
+                    }
+                };
+                total_extracted_size += written;
+                Ok(())
+            })();
+
+            match result {
+                Ok(()) => report.recovered.push(entry.name.clone()),
+                Err(reason) => {
+                    log::warn!("Could not salvage {}: {}", entry.name, reason);
+                    report.lost.push(LostEntry {
+                        name: entry.name.clone(),
+                        reason,
+                    });
+                }
+            }
+
+            let progress = ((i + 1) as f64 / total as f64 * 100.0) as u8;
+            emit_progress(&app_handle, progress).map_err(|e| e.to_string())?;
+        }
+
+        emit_status(
+            &app_handle,
+            format!("{} fichier(s) récupéré(s), {} perdu(s)", report.recovered.len(), report.lost.len()),
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(report)
+    }).await.map_err(|e| e.to_string())?
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OutputDirStatus {
+    exists: bool,
+    writable: bool,
+    // Measured on the closest existing ancestor when the directory isn't created yet
+    free_bytes: Option<u64>,
+}
+
+// Never errors: the UI shows a warning from the flags instead of blocking
+#[tauri::command]
+fn check_output_dir(path: String) -> OutputDirStatus {
+    let dir = Path::new(&path);
+    let exists = dir.is_dir();
+
+    // Permission bits lie on network shares and read-only mounts, an actual write doesn't
+    let writable = exists && tempfile::Builder::new().prefix(".eazip-probe").tempfile_in(dir).is_ok();
+
+    let free_bytes = dir
+        .ancestors()
+        .find(|p| p.is_dir())
+        .and_then(|p| fs2::available_space(p).ok());
+
+    OutputDirStatus {
+        exists,
+        writable,
+        free_bytes,
+    }
+}
+
+// The 7z entry reader reports a CRC mismatch as an I/O error wrapping the crate's own
+fn is_7z_checksum_error(e: &std::io::Error) -> bool {
+    matches!(
+        e.get_ref().and_then(|inner| inner.downcast_ref::<sevenz_rust2::Error>()),
+        Some(sevenz_rust2::Error::ChecksumVerificationFailed)
+    )
+}
+
+// Decrypts one encrypted entry to its end, so a match is confirmed by the CRC/HMAC
+// check rather than the 1-2 byte password verifier alone: the smallest non-empty one
+// for zip, the first non-empty one in an AES block for 7z, the first encrypted file
+// for RAR. Errors that say nothing about the password are returned as errors.
+fn password_matches(path: &Path, password: &str) -> Result<bool, String> {
+    if is_7z_path(path) {
+        let headers_encrypted = matches!(
+            sevenz_rust2::Archive::open(path),
+            Err(sevenz_rust2::Error::PasswordRequired)
+        );
+        let mut reader = match sevenz_rust2::ArchiveReader::open(path, password.into()) {
+            Ok(reader) => reader,
+            // Encrypted headers decode to garbage with a wrong key, whatever the exact error
+            Err(_) if headers_encrypted => return Ok(false),
+            Err(e) => return Err(e.to_string()),
+        };
+        let archive = reader.archive();
+        let encrypted: std::collections::HashSet<String> = archive
+            .files
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| !f.is_directory() && f.size() > 0)
+            .filter(|(i, _)| {
+                archive
+                    .stream_map
+                    .file_block_index
+                    .get(*i)
+                    .copied()
+                    .flatten()
+                    .and_then(|b| archive.blocks.get(b))
+                    .is_some_and(|block| {
+                        block
+                            .coders
+                            .iter()
+                            .any(|c| c.encoder_method_id() == sevenz_rust2::EncoderMethod::ID_AES256SHA256)
+                    })
+            })
+            .map(|(_, f)| f.name().to_string())
+            .collect();
+        if encrypted.is_empty() {
+            // Nothing is encrypted, any password "opens" it
+            return Ok(true);
+        }
+        let res = reader.for_each_entries(|entry, entry_reader| {
+            if !encrypted.contains(entry.name()) {
+                return Ok(true);
+            }
+            std::io::copy(entry_reader, &mut std::io::sink()).map_err(|e| {
+                // Data decrypted with the wrong key fails to decompress or to match its CRC
+                if e.kind() == std::io::ErrorKind::InvalidData || is_7z_checksum_error(&e) {
+                    sevenz_rust2::Error::MaybeBadPassword(e)
+                } else {
+                    sevenz_rust2::Error::Io(e, "Verify entry".into())
+                }
+            })?;
+            // One decoded entry is enough
+            Ok(false)
+        });
+        return match res {
+            Ok(()) => Ok(true),
+            Err(
+                sevenz_rust2::Error::PasswordRequired
+                | sevenz_rust2::Error::MaybeBadPassword(_)
+                | sevenz_rust2::Error::ChecksumVerificationFailed,
+            ) => Ok(false),
+            Err(sevenz_rust2::Error::Io(e, _)) if is_7z_checksum_error(&e) => Ok(false),
+            Err(e) => Err(e.to_string()),
+        };
+    }
+
+    if is_rar_path(path) {
+        let mut archive = unrar::Archive::with_password(path, password)
+            .open_for_processing()
+            .map_err(rar_error)?;
+        loop {
+            let header = match archive.read_header() {
+                Ok(Some(header)) => header,
+                // Nothing is encrypted, any password "opens" it
+                Ok(None) => return Ok(true),
+                // Encrypted headers fail here already
+                Err(e) if e.code == unrar::error::Code::BadPassword => return Ok(false),
+                Err(e) => return Err(rar_error(e)),
+            };
+            if header.entry().is_directory() || !header.entry().is_encrypted() {
+                archive = header.skip().map_err(rar_error)?;
+                continue;
+            }
+            // RAR5 checks the password itself, RAR4 only notices through the CRC
+            return match header.test() {
+                Ok(_) => Ok(true),
+                Err(e) if matches!(e.code, unrar::error::Code::BadPassword | unrar::error::Code::BadData) => Ok(false),
+                Err(e) => Err(rar_error(e)),
+            };
+        }
+    }
+
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| zip_error_message(path, e))?;
+    // The smallest entry with data: an empty one has no CRC or HMAC to check once read,
+    // so only the ZipCrypto header byte would vouch for the password (1 in 256 false matches).
+    // Empty entries are only used when there is nothing else.
+    let mut smallest: Option<(usize, (bool, u64))> = None;
+    for i in 0..archive.len() {
+        let entry = archive.by_index_raw(i).map_err(|e| e.to_string())?;
+        let key = (entry.size() == 0, entry.size());
+        if entry.encrypted() && !entry.is_dir() && smallest.map_or(true, |(_, best)| key < best) {
+            smallest = Some((i, key));
+        }
+    }
+    let Some((index, _)) = smallest else {
+        // Nothing is encrypted, any password "opens" it
+        return Ok(true);
+    };
+
+    let mut entry = match archive.by_index_decrypt(index, password.as_bytes()) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::InvalidPassword) => return Ok(false),
+        Err(e) => return Err(zip_error_message(path, e)),
+    };
+    // Read to the end, that's where the zip crate checks the CRC and the AES HMAC
+    Ok(std::io::copy(&mut entry, &mut std::io::sink()).is_ok())
+}
+
+#[tauri::command]
+async fn verify_password(file_path: String, password: Secret<String>) -> Result<bool, String> {
+    let password = password.expose_secret().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        password_matches(Path::new(&file_path), &password)
+    }).await.map_err(|e| e.to_string())?
+}
+
+// Recovery helper for "which of my usual passwords was it", not a cracker:
+// the list is kept short on purpose
+const MAX_PASSWORD_CANDIDATES: usize = 20;
+
+#[tauri::command]
+async fn try_passwords(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    file_path: String,
+    candidates: Vec<Secret<String>>,
+    job_id: Option<JobId>,
+) -> Result<Option<usize>, String> {
+    if candidates.len() > MAX_PASSWORD_CANDIDATES {
+        return Err(format!("Too many candidate passwords (limit: {})", MAX_PASSWORD_CANDIDATES));
+    }
+    let job = state.jobs.start(JobKind::Decrypt, job_id)?;
+    let cancel_flag = job.cancel_flag();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let _job = job.enter();
+        let path = Path::new(&file_path);
+
+        for (i, candidate) in candidates.iter().enumerate() {
+            if cancel_flag.load(Ordering::SeqCst) {
+                return Err("Password search cancelled by user.".to_string());
+            }
+            emit_status(&app_handle, format!("Essai du mot de passe {}/{}...", i + 1, candidates.len())).map_err(|e| e.to_string())?;
+
+            if password_matches(path, candidate.expose_secret())? {
+                return Ok(Some(i));
+            }
+            let progress = ((i + 1) as f64 / candidates.len() as f64 * 100.0) as u8;
+            emit_progress(&app_handle, progress).map_err(|e| e.to_string())?;
+        }
+        Ok(None)
+    }).await.map_err(|e| e.to_string())?
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PasswordMatch {
+    path: String,
+    matches: bool,
+    // Set when the archive couldn't be checked at all (missing, corrupt...)
+    error: Option<String>,
+}
+
+#[tauri::command]
+async fn find_matching_archives(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    paths: Vec<String>,
+    password: Secret<String>,
+    job_id: Option<JobId>,
+) -> Result<Vec<PasswordMatch>, String> {
+    let job = state.jobs.start(JobKind::Decrypt, job_id)?;
+    let cancel_flag = job.cancel_flag();
+    let password = password.expose_secret().clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let _job = job.enter();
+        let mut results = Vec::with_capacity(paths.len());
+
+        for (i, path) in paths.iter().enumerate() {
+            if cancel_flag.load(Ordering::SeqCst) {
+                return Err("Password search cancelled by user.".to_string());
+            }
+            emit_status(&app_handle, format!("Vérification ({}/{})...", i + 1, paths.len())).map_err(|e| e.to_string())?;
+
+            let (matches, error) = match password_matches(Path::new(path), &password) {
+                Ok(matches) => (matches, None),
+                Err(e) => (false, Some(e)),
+            };
+            results.push(PasswordMatch { path: path.clone(), matches, error });
+
+            let progress = ((i + 1) as f64 / paths.len() as f64 * 100.0) as u8;
+            emit_progress(&app_handle, progress).map_err(|e| e.to_string())?;
+        }
+
+        Ok(results)
+    }).await.map_err(|e| e.to_string())?
+}
+
+// The benchmark compresses at most this much of the sample, per combination
+const BENCHMARK_SAMPLE_LIMIT: u64 = 4 * 1024 * 1024;
+// Combinations still waiting once this is spent are reported as skipped
+const BENCHMARK_TIME_BUDGET: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy, serde::Deserialize, serde::Serialize)]
+enum BenchMethod {
+    Stored,
+    Deflated,
+    Bzip2,
+    Zstd,
+}
+
+impl BenchMethod {
+    // Levels the zip writer accepts, minus the zopfli range of Deflate (10 and up)
+    // which takes minutes per megabyte and would blow any time budget
+    fn level_range(self) -> Option<std::ops::RangeInclusive<i64>> {
+        match self {
+            BenchMethod::Stored => None,
+            BenchMethod::Deflated => Some(0..=9),
+            BenchMethod::Bzip2 => Some(1..=9),
+            BenchMethod::Zstd => Some(-7..=22),
+        }
+    }
+}
+
+impl From<BenchMethod> for CompressionMethod {
+    fn from(method: BenchMethod) -> Self {
+        match method {
+            BenchMethod::Stored => CompressionMethod::Stored,
+            BenchMethod::Deflated => CompressionMethod::Deflated,
+            BenchMethod::Bzip2 => CompressionMethod::Bzip2,
+            BenchMethod::Zstd => CompressionMethod::Zstd,
+        }
+    }
+}
+
+// Compression given as "method[:level]" for scripting: store, deflate (0-9), bzip2 (1-9)
+// or zstd (-7 to 22) for zip, 7z (0-9, the LZMA2 preset) for 7z. Deflate levels from 10
+// (zopfli) are left out as in the benchmark.
+#[derive(Clone, Copy)]
+enum CompressionSpec {
+    Zip(BenchMethod, Option<i64>),
+    SevenZip(Option<u32>),
+}
+
+fn parse_compression_spec(spec: &str) -> Result<CompressionSpec, String> {
+    let (name, level) = match spec.split_once(':') {
+        Some((name, level)) => (name, Some(level)),
+        None => (spec, None),
+    };
+    let level = level
+        .map(|level| level.trim().parse::<i64>().map_err(|_| format!("Invalid compression level: {}", level)))
+        .transpose()?;
+    let method = match name.trim().to_ascii_lowercase().as_str() {
+        "7z" | "lzma2" => {
+            return match level {
+                Some(level) if !(0..=9).contains(&level) => Err("Level must be between 0 and 9 for 7z".to_string()),
+                level => Ok(CompressionSpec::SevenZip(level.map(|level| level as u32))),
+            };
+        }
+        "store" | "stored" => BenchMethod::Stored,
+        "deflate" | "deflated" => BenchMethod::Deflated,
+        "bzip2" => BenchMethod::Bzip2,
+        "zstd" => BenchMethod::Zstd,
+        _ => {
+            return Err(format!(
+                "Unknown compression method: {} (expected store, deflate, bzip2, zstd or 7z)",
+                name.trim()
+            ))
+        }
+    };
+    match (level, method.level_range()) {
+        (Some(_), None) => Err("Store takes no compression level".to_string()),
+        (Some(level), Some(range)) if !range.contains(&level) => {
+            Err(format!("Level must be between {} and {} for this method", range.start(), range.end()))
+        }
+        _ => Ok(CompressionSpec::Zip(method, level)),
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BenchResult {
+    method: BenchMethod,
+    level: i64,
+    mb_per_sec: f64,
+    // Compressed size over original size, lower is better
+    ratio: f64,
+    // Unsupported level for the method, or skipped after the time budget ran out
+    error: Option<String>,
+}
+
+fn compress_sample(sample: &[u8], method: BenchMethod, level: i64) -> Result<(f64, f64), String> {
+    // Stored has no levels, any requested level just measures the plain copy
+    let level = match method.level_range() {
+        Some(range) if !range.contains(&level) => {
+            return Err(format!("Level must be between {} and {} for this method", range.start(), range.end()));
+        }
+        Some(_) => Some(level),
+        None => None,
+    };
+    let options: FileOptions<'_, ()> = FileOptions::default()
+        .compression_method(method.into())
+        .compression_level(level);
+    let started = Instant::now();
+    let mut zip = ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    zip.start_file("sample", options).map_err(|e| e.to_string())?;
+    zip.write_all(sample).map_err(|e| e.to_string())?;
+    let written = zip.finish().map_err(|e| e.to_string())?;
+    let elapsed = started.elapsed().as_secs_f64().max(1e-6);
+
+    let mut archive = zip::ZipArchive::new(written).map_err(|e| e.to_string())?;
+    let compressed = archive.by_index_raw(0).map_err(|e| e.to_string())?.compressed_size();
+    let mb_per_sec = sample.len() as f64 / (1024.0 * 1024.0) / elapsed;
+    let ratio = if sample.is_empty() { 1.0 } else { compressed as f64 / sample.len() as f64 };
+    Ok((mb_per_sec, ratio))
+}
+
+#[tauri::command]
+async fn benchmark_compression(
+    sample_path: String,
+    methods: Vec<BenchMethod>,
+    levels: Vec<i64>,
+) -> Result<Vec<BenchResult>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut sample = Vec::new();
+        File::open(&sample_path)
+            .map_err(|e| format!("Failed to open sample: {}", e))?
+            .take(BENCHMARK_SAMPLE_LIMIT)
+            .read_to_end(&mut sample)
+            .map_err(|e| format!("Failed to read sample: {}", e))?;
+
+        let started = Instant::now();
+        let mut results = Vec::with_capacity(methods.len() * levels.len());
+        for &method in &methods {
+            for &level in &levels {
+                let outcome = if started.elapsed() > BENCHMARK_TIME_BUDGET {
+                    Err("Skipped, benchmark time budget exceeded".to_string())
+                } else {
+                    compress_sample(&sample, method, level)
+                };
+                results.push(match outcome {
+                    Ok((mb_per_sec, ratio)) => BenchResult { method, level, mb_per_sec, ratio, error: None },
+                    Err(e) => BenchResult { method, level, mb_per_sec: 0.0, ratio: 0.0, error: Some(e) },
+                });
+            }
+        }
+        Ok(results)
+    }).await.map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+fn get_archive_comment(file_path: String) -> Result<Option<String>, String> {
+    let path = Path::new(&file_path);
+
+    // sevenz_rust2 doesn't expose archive comments, 7z archives never report one
+    if is_7z_path(path) {
+        return Ok(None);
+    }
+
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+    let comment = String::from_utf8_lossy(archive.comment()).trim().to_string();
+
+    Ok(if comment.is_empty() { None } else { Some(comment) })
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArchiveIndex<'a> {
+    format: &'static str,
+    comment: Option<String>,
+    entries: &'a [ArchiveEntry],
+}
+
+// Catalog of the archive as JSON, from the listing alone: no entry data is decrypted
+// or extracted. RAR comments aren't read, like 7z ones.
+#[tauri::command]
+async fn export_manifest(
+    file_path: String,
+    password: Option<Secret<String>>,
+    output_json: String,
+) -> Result<String, String> {
+    let password = password.map(|p| p.expose_secret().clone()).unwrap_or_default();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = Path::new(&file_path);
+        let entries = read_archive_entries(path, &password)?;
+        let comment = if is_rar_path(path) { None } else { get_archive_comment(file_path.clone())? };
+        let index = ArchiveIndex {
+            format: archive_format(path),
+            comment,
+            entries: &entries,
+        };
+
+        let file = File::create(&output_json).map_err(|e| format!("Failed to create {}: {}", output_json, e))?;
+        let mut writer = std::io::BufWriter::new(file);
+        serde_json::to_writer_pretty(&mut writer, &index).map_err(|e| e.to_string())?;
+        writer.flush().map_err(|e| format!("Failed to write {}: {}", output_json, e))?;
+        Ok(format!("Index of {} entries written to: {}", entries.len(), output_json))
+    }).await.map_err(|e| e.to_string())?
+}
+
+// Read from the archive comment, no password needed
+#[tauri::command]
+fn get_password_hint(file_path: String) -> Result<Option<String>, String> {
+    let comment = get_archive_comment(file_path)?;
+    Ok(comment.and_then(|c| c.lines().find_map(|line| line.strip_prefix(PASSWORD_HINT_LABEL)).map(str::to_string)))
+}
+
+// Id to pass to a long-running command before starting it, so it can be cancelled
+// and polled on its own
+#[tauri::command]
+fn new_job_id(state: tauri::State<'_, AppState>) -> JobId {
+    state.jobs.new_id()
+}
+
+// Latest progress and status of a running job; None once it has finished
+#[tauri::command]
+fn get_progress(state: tauri::State<'_, AppState>, job_id: JobId) -> Option<ProgressSnapshot> {
+    state.jobs.progress(job_id)
+}
+
+#[tauri::command]
+fn cancel_job(state: tauri::State<'_, AppState>, job_id: JobId) -> bool {
+    state.jobs.cancel(job_id)
+}
+
+// Without a job id these stop every running job of their kind
+#[tauri::command]
+fn cancel_encryption(state: tauri::State<'_, AppState>, job_id: Option<JobId>) {
+    match job_id {
+        Some(id) => {
+            state.jobs.cancel(id);
+        }
+        None => state.jobs.cancel_kind(JobKind::Encrypt),
+    }
+}
+
+#[tauri::command]
+fn cancel_decryption(state: tauri::State<'_, AppState>, job_id: Option<JobId>) {
+    match job_id {
+        Some(id) => {
+            state.jobs.cancel(id);
+        }
+        None => state.jobs.cancel_kind(JobKind::Decrypt),
+    }
+}
+
+#[tauri::command]
+fn cancel_selection_summary(state: tauri::State<'_, AppState>, job_id: Option<JobId>) {
+    match job_id {
+        Some(id) => {
+            state.jobs.cancel(id);
+        }
+        None => state.jobs.cancel_kind(JobKind::Selection),
+    }
+}
+
+// Release builds run without a console on Windows; EAZIP_CONSOLE=1 attaches to the
+// launching terminal (or opens a new one) so logs and panics can be captured
+#[cfg(windows)]
+fn attach_debug_console() {
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+        fn AllocConsole() -> i32;
+    }
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    if std::env::var_os("EAZIP_CONSOLE").is_some_and(|v| v == "1") {
+        unsafe {
+            if AttachConsole(ATTACH_PARENT_PROCESS) == 0 {
+                AllocConsole();
+            }
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_debug_console() {}
+
+fn main() {
+    attach_debug_console();
+
+    let app_state = AppState {
+        jobs: JobRegistry::default(),
+        skip_events: Mutex::new(SkipThrottle::default()),
+    };
+
+    tauri::Builder::default()
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_log::Builder::default().build())
+        .manage(app_state)
+        .invoke_handler(tauri::generate_handler![
+            generate_password,
+            generate_passwords,
+            get_defaults,
+            encrypt_files,
+            encrypt_bytes,
+            decrypt_file,
+            store_archive_password,
+            forget_archive_password,
+            list_archive,
+            search_archive,
+            largest_entries,
+            inspect_archive,
+            get_encryption_info,
+            get_archive_comment,
+            export_manifest,
+            get_password_hint,
+            diff_archives,
+            compare_archive_to_dir,
+            extract_entry_to_stream,
+            salvage_archive,
+            check_output_dir,
+            cleanup_temp,
+            save_profile,
+            load_profile,
+            list_profiles,
+            build_manifest,
+            verify_password,
+            try_passwords,
+            find_matching_archives,
+            benchmark_compression,
+            new_job_id,
+            get_progress,
+            cancel_job,
+            cancel_encryption,
+            cancel_decryption,
+            cancel_selection_summary,
+            convert_archive,
+            merge_archives,
+            rename_entry,
+            archive_health,
+            get_entry_thumbnail,
+            preview_text_entry,
+            read_entry_range,
+            verify_file_checksum,
+            summarize_selection,
+            find_duplicates,
+            get_file_metadata,
+            validate_sources
+        ])
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSWORD: &str = "correct horse battery";
+
+    fn write_files(dir: &Path, files: &[(&str, &[u8])]) {
+        for (name, data) in files {
+            let path = dir.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, data).unwrap();
+        }
+    }
+
+    fn encrypt(
+        app: &tauri::App<tauri::test::MockRuntime>,
+        input: &Path,
+        output: &Path,
+        method: EncryptionMethod,
+        options: &EncryptOptions,
+    ) -> Result<EncryptionSummary, String> {
+        let inputs = [input.to_str().unwrap().to_string()];
+        encrypt_to_path(app.handle(), &AtomicBool::new(false), &inputs, output.to_str().unwrap(), PASSWORD, &method, options)
+    }
+
+    fn extract(
+        app: &tauri::App<tauri::test::MockRuntime>,
+        archive: &Path,
+        output_dir: &Path,
+        password: &str,
+        options: &DecryptOptions,
+    ) -> Result<String, String> {
+        fs::create_dir_all(output_dir).unwrap();
+        let mut skipped = SkippedEntries::new(options);
+        extract_archive(app.handle(), &AtomicBool::new(false), archive, output_dir.to_str().unwrap(), password, options, &mut skipped)
+    }
+
+    // Entries below the selected folder are named after it
+    fn extracted(output_dir: &Path, input: &Path, name: &str) -> Vec<u8> {
+        fs::read(output_dir.join(input.file_name().unwrap()).join(name)).unwrap()
+    }
+
+    #[test]
+    fn truncated_zip_lists_readable_entries_and_flags_the_damaged_one() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("truncated.zip");
+        let mut zip = ZipWriter::new(File::create(&path).unwrap());
+        for name in ["a.txt", "b.txt"] {
+            zip.start_file(name, FileOptions::<()>::default().compression_method(CompressionMethod::Stored)).unwrap();
+            zip.write_all(&[b'x'; 4096]).unwrap();
+        }
+        zip.finish().unwrap();
+        // Cut in the middle of the second entry's data, the central directory is gone
+        let len = fs::metadata(&path).unwrap().len();
+        File::options().write(true).open(&path).unwrap().set_len(len - 4096).unwrap();
+
+        let listed = list_archive(path.to_str().unwrap().to_string(), None);
+        let entries = tauri::async_runtime::block_on(listed).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name, "a.txt");
+        assert!(entries[0].error.is_none());
+        assert_eq!(entries[1].name, "b.txt");
+        assert_eq!(entries[1].error.as_deref(), Some("Entry data truncated"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn broken_symlinks_follow_the_policy() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input");
+        fs::create_dir(&input).unwrap();
+        fs::write(input.join("kept.txt"), b"data").unwrap();
+        std::os::unix::fs::symlink(input.join("missing"), input.join("dangling")).unwrap();
+        let app = tauri::test::mock_app();
+        let collect = |policy| {
+            let options = EncryptOptions { broken_symlink_policy: policy, ..Default::default() };
+            let inputs = [input.to_str().unwrap().to_string()];
+            collect_entries(app.handle(), &inputs, &dir.path().join("out.zip"), &options, &IoRetry::new(&options))
+                .map(|(entries, ..)| entries)
+        };
+        let names = |entries: &[CollectedEntry]| -> Vec<std::path::PathBuf> {
+            entries.iter().filter(|e| !e.is_dir).map(|e| e.rel_path.clone()).collect()
+        };
+
+        let skipped = collect(BrokenSymlinkPolicy::Skip).unwrap();
+        assert_eq!(names(&skipped), [Path::new("input/kept.txt")]);
+
+        let error = collect(BrokenSymlinkPolicy::Error).err().unwrap();
+        assert!(error.starts_with("Broken symlink"), "{}", error);
+
+        let linked = collect(BrokenSymlinkPolicy::StoreAsLink).unwrap();
+        let link = linked.iter().find(|e| e.link_target.is_some()).unwrap();
+        assert_eq!(link.rel_path, Path::new("input/dangling"));
+        assert_eq!(link.link_target.as_deref(), Some(input.join("missing").as_path()));
+    }
+
+    #[test]
+    fn cancelled_7z_extraction_stops_before_writing_the_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input");
+        fs::create_dir(&input).unwrap();
+        let data: Vec<u8> = (0..3 * 1024 * 1024).map(|_| rand::random::<u8>()).collect();
+        fs::write(input.join("big.bin"), &data).unwrap();
+        let archive = dir.path().join("out.7z");
+        sevenz_rust2::compress_to_path_encrypted(&input, &archive, "secret".into()).unwrap();
+
+        let output = dir.path().join("extracted");
+        fs::create_dir(&output).unwrap();
+        let app = tauri::test::mock_app();
+        let cancel_flag = AtomicBool::new(true);
+        let started = Instant::now();
+        let options = DecryptOptions::default();
+        let mut skipped = SkippedEntries::new(&options);
+        let result = extract_7z_guarded(app.handle(), &cancel_flag, &archive, &output, "secret", &options, &mut skipped);
+        assert_eq!(result.err().as_deref(), Some("Decryption cancelled by user."));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        let written = fs::metadata(output.join("big.bin")).map_or(0, |m| m.len());
+        assert!(written < data.len() as u64);
+    }
+
+    #[test]
+    fn parallel_compression_round_trips_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input");
+        let files: Vec<(String, Vec<u8>)> = (0..24)
+            .map(|i| (format!("dir{}/file{:02}.txt", i % 3, i), format!("content {} ", i).repeat(1000 * (i + 1)).into_bytes()))
+            .collect();
+        let refs: Vec<(&str, &[u8])> = files.iter().map(|(n, d)| (n.as_str(), d.as_slice())).collect();
+        write_files(&input, &refs);
+        let archive = dir.path().join("out.zip");
+        let options = EncryptOptions { parallel_workers: 4, ..Default::default() };
+        let inputs = [input.to_str().unwrap().to_string()];
+        let app = tauri::test::mock_app();
+        let (mut entries, ..) = collect_entries(app.handle(), &inputs, &archive, &options, &IoRetry::new(&options)).unwrap();
+        sort_entries(&mut entries);
+
+        let file_options = zip_file_options(&EncryptionMethod::Aes256, PASSWORD);
+        let mut zip = ZipWriter::new(File::create(&archive).unwrap());
+        write_zip_parallel(app.handle(), &AtomicBool::new(false), &mut zip, &entries, &options, &IoRetry::new(&options), &|_| file_options.clone()).unwrap();
+        zip.finish().unwrap();
+
+        let mut archive = zip::ZipArchive::new(File::open(&archive).unwrap()).unwrap();
+        let names: Vec<String> = archive.file_names().map(|n| n.to_string()).collect();
+        let expected: Vec<String> = entries.iter().map(|e| e.rel_path.to_string_lossy().replace('\\', "/")).collect();
+        assert_eq!(names.iter().map(|n| n.trim_end_matches('/')).collect::<Vec<_>>(), expected);
+        for (name, data) in &files {
+            let mut content = Vec::new();
+            archive.by_name_decrypt(&format!("input/{}", name), PASSWORD.as_bytes()).unwrap().read_to_end(&mut content).unwrap();
+            assert_eq!(&content, data, "{}", name);
+        }
+    }
+
+    #[test]
+    fn unsupported_zip_encryption_is_named() {
+        let entry = |flags, method, aes_strength| ScannedEntry {
+            name: "a.txt".to_string(),
+            header_offset: 0,
+            data_start: 0,
+            flags,
+            method,
+            aes_strength,
+            crc32: 0,
+            compressed_size: 0,
+            size: 0,
+            error: None,
+        };
+        assert_eq!(
+            unsupported_encryption(&entry(0x2001, 99, Some(1))).as_deref(),
+            Some("Unsupported encryption (AES-128 header-encrypted)")
+        );
+        assert_eq!(
+            unsupported_encryption(&entry(0x0041, 8, None)).as_deref(),
+            Some("Unsupported encryption (PKWARE strong encryption)")
+        );
+        assert_eq!(
+            unsupported_encryption(&entry(0x0001, 99, None)).as_deref(),
+            Some("Malformed AES entry (missing AES extra field)")
+        );
+        assert_eq!(unsupported_encryption(&entry(0x0001, 99, Some(3))), None);
+        assert_eq!(unsupported_encryption(&entry(0x0001, 8, None)), None);
+    }
+
+    #[test]
+    fn aes_entries_written_here_are_recognised() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("out.zip");
+        let mut zip = ZipWriter::new(File::create(&archive).unwrap());
+        zip.start_file("a.txt", zip_file_options(&EncryptionMethod::Aes256, PASSWORD)).unwrap();
+        zip.write_all(&[b'a'; 100]).unwrap();
+        zip.finish().unwrap();
+
+        let scanned = scan_local_headers(&mut File::open(&archive).unwrap()).unwrap();
+        let entry = scanned.iter().find(|e| e.name == "a.txt").unwrap();
+        assert_eq!((entry.method, entry.aes_strength), (99, Some(3)));
+        assert_eq!(unsupported_encryption(entry), None);
+    }
+
+    #[test]
+    fn case_only_collisions_follow_the_policy() {
+        let app = tauri::test::mock_app();
+        let dir = tempfile::tempdir().unwrap();
+        // What a case-insensitive target looks like once File.txt is written
+        write_files(dir.path(), &[("File.txt", b"first"), ("file.txt", b"first")]);
+        let collisions = || CaseCollisions { enabled: true, seen: std::collections::HashSet::new() };
+        let resolve = |collisions: &mut CaseCollisions, name: &str, policy| {
+            collisions.resolve(app.handle(), dir.path().join(name), policy)
+        };
+
+        let mut rename = collisions();
+        assert_eq!(resolve(&mut rename, "File.txt", CollisionPolicy::Rename), Some(dir.path().join("File.txt")));
+        assert_eq!(resolve(&mut rename, "file.txt", CollisionPolicy::Rename), Some(dir.path().join("file (2).txt")));
+
+        let mut skip = collisions();
+        resolve(&mut skip, "File.txt", CollisionPolicy::Skip);
+        assert_eq!(resolve(&mut skip, "file.txt", CollisionPolicy::Skip), None);
+
+        let mut overwrite = collisions();
+        resolve(&mut overwrite, "File.txt", CollisionPolicy::Overwrite);
+        assert_eq!(resolve(&mut overwrite, "file.txt", CollisionPolicy::Overwrite), Some(dir.path().join("file.txt")));
+    }
+
+    #[test]
+    fn restore_dir_times_sets_directory_mtime() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("nested");
+        fs::create_dir(&dir).unwrap();
+        let mtime = std::time::UNIX_EPOCH + Duration::from_secs(1_500_000_000);
+
+        restore_dir_times(vec![(dir.clone(), mtime)]);
+        // Restored last, a file written inside afterwards would have bumped it
+        assert_eq!(fs::metadata(&dir).unwrap().modified().unwrap(), mtime);
+    }
+
+    #[test]
+    fn job_progress_is_kept_per_job_and_dropped_when_it_ends() {
+        let jobs = JobRegistry::default();
+        let first = jobs.start(JobKind::Encrypt, None).unwrap();
+        let second = jobs.start(JobKind::Decrypt, Some(100)).unwrap();
+        assert!(jobs.start(JobKind::Decrypt, Some(100)).is_err());
+
+        {
+            let _current = first.enter();
+            update_current_job(|snapshot| snapshot.progress = 40);
+            let _nested = second.enter();
+            update_current_job(|snapshot| snapshot.progress = 70);
+        }
+        update_current_job(|snapshot| snapshot.progress = 99);
+        assert_eq!(jobs.progress(first.id).unwrap().progress, 40);
+        assert_eq!(jobs.progress(100).unwrap().progress, 70);
+
+        let first_id = first.id;
+        drop(first);
+        assert!(jobs.progress(first_id).is_none());
+        assert!(jobs.progress(100).is_some());
+    }
+
+    #[test]
+    fn unsafe_entry_names_are_detected() {
+        for name in ["/etc/passwd", "C:\\Windows\\evil.dll", "c:/evil", "a/../../b", "..\\up.txt"] {
+            assert!(is_unsafe_entry_name(name), "{}", name);
+        }
+        for name in ["a/b.txt", "a..b/c", "dir/..hidden", "./a.txt"] {
+            assert!(!is_unsafe_entry_name(name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn reject_absolute_paths_refuses_the_archive_before_writing() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["../evil.txt", "/tmp/evil.txt"] {
+            let archive = dir.path().join("unsafe.zip");
+            let mut zip = ZipWriter::new(File::create(&archive).unwrap());
+            zip.start_file("safe.txt", FileOptions::<()>::default()).unwrap();
+            zip.write_all(b"safe").unwrap();
+            zip.start_file(name, FileOptions::<()>::default()).unwrap();
+            zip.write_all(b"evil").unwrap();
+            zip.finish().unwrap();
+
+            let output = dir.path().join("extracted");
+            let options = DecryptOptions { reject_absolute_paths: true, ..Default::default() };
+            let error = extract(&tauri::test::mock_app(), &archive, &output, "", &options).err().unwrap();
+            assert_eq!(error, format!("Archive refused, entry has an absolute or parent path: {}", name));
+            assert_eq!(fs::read_dir(&output).unwrap().count(), 0);
+        }
+    }
+
+    #[test]
+    fn progress_follows_entries_when_there_are_no_bytes() {
+        let app = tauri::test::mock_app();
+        let mut progress = ProgressTracker::bytes_or_entries(app.handle(), 0, 4);
+        let mut last = 0;
+        for _ in 0..4 {
+            progress.finish_entry();
+            assert!(progress.last_progress_percent > last);
+            last = progress.last_progress_percent;
+        }
+    }
+
+    #[test]
+    fn tree_of_empty_files_reaches_full_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input");
+        write_files(&input, &[("a.txt", b""), ("sub/b.txt", b""), ("sub/deeper/c.txt", b"")]);
+        let app = tauri::test::mock_app();
+        let jobs = JobRegistry::default();
+        for (method, name) in [(EncryptionMethod::Aes256, "out.zip"), (EncryptionMethod::SevenZip, "out.7z")] {
+            let job = jobs.start(JobKind::Encrypt, None).unwrap();
+            let _current = job.enter();
+            encrypt(&app, &input, &dir.path().join(name), method, &EncryptOptions::default()).unwrap();
+            assert_eq!(jobs.progress(job.id).unwrap().progress, 100, "{}", name);
+        }
+    }
+
+    #[test]
+    fn cancelling_one_job_leaves_the_others_running() {
+        let jobs = JobRegistry::default();
+        let encrypt = jobs.start(JobKind::Encrypt, None).unwrap();
+        let first = jobs.start(JobKind::Decrypt, None).unwrap();
+        let second = jobs.start(JobKind::Decrypt, None).unwrap();
+
+        assert!(jobs.cancel(first.id));
+        assert!(first.cancel_flag().load(Ordering::SeqCst));
+        assert!(!second.cancel_flag().load(Ordering::SeqCst));
+        assert!(!encrypt.cancel_flag().load(Ordering::SeqCst));
+
+        jobs.cancel_kind(JobKind::Decrypt);
+        assert!(second.cancel_flag().load(Ordering::SeqCst));
+        assert!(!encrypt.cancel_flag().load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn cancelling_a_decryption_lets_a_concurrent_encryption_finish() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input");
+        let data: Vec<u8> = (0..3 * 1024 * 1024).map(|_| rand::random::<u8>()).collect();
+        write_files(&input, &[("big.bin", &data)]);
+        let sealed = dir.path().join("sealed.7z");
+        let app = tauri::test::mock_app();
+        encrypt(&app, &input, &sealed, EncryptionMethod::SevenZip, &EncryptOptions::default()).unwrap();
+
+        let jobs = JobRegistry::default();
+        let encrypt_job = jobs.start(JobKind::Encrypt, None).unwrap();
+        let decrypt_job = jobs.start(JobKind::Decrypt, None).unwrap();
+        assert!(jobs.cancel(decrypt_job.id));
+
+        let archive = dir.path().join("out.zip");
+        let output = dir.path().join("extracted");
+        fs::create_dir_all(&output).unwrap();
+        let handle = app.handle();
+        let (encrypted, decrypted) = std::thread::scope(|scope| {
+            let encrypting = scope.spawn(|| {
+                let _job = encrypt_job.enter();
+                let inputs = [input.to_str().unwrap().to_string()];
+                let options = EncryptOptions::default();
+                encrypt_to_path(handle, &encrypt_job.cancel_flag(), &inputs, archive.to_str().unwrap(), PASSWORD, &EncryptionMethod::Aes256, &options)
+            });
+            let decrypting = scope.spawn(|| {
+                let _job = decrypt_job.enter();
+                let options = DecryptOptions::default();
+                let mut skipped = SkippedEntries::new(&options);
+                extract_archive(handle, &decrypt_job.cancel_flag(), &sealed, output.to_str().unwrap(), PASSWORD, &options, &mut skipped)
+            });
+            (encrypting.join().unwrap(), decrypting.join().unwrap())
+        });
+
+        assert_eq!(decrypted.err().as_deref(), Some("Decryption cancelled by user."));
+        encrypted.unwrap();
+        assert!(!encrypt_job.cancel_flag().load(Ordering::SeqCst));
+        assert_eq!(jobs.progress(encrypt_job.id).unwrap().progress, 100);
+
+        let check = dir.path().join("check");
+        extract(&app, &archive, &check, PASSWORD, &DecryptOptions::default()).unwrap();
+        assert_eq!(extracted(&check, &input, "big.bin"), data);
+    }
+
+    #[test]
+    fn output_selected_as_input_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("out.zip");
+        fs::write(&archive, b"previous run").unwrap();
+        let app = tauri::test::mock_app();
+        let error = encrypt(&app, &archive, &archive, EncryptionMethod::Aes256, &EncryptOptions::default()).err().unwrap();
+        assert_eq!(error, format!("{} is both an input and the output archive", archive.display()));
+        assert_eq!(fs::read(&archive).unwrap(), b"previous run");
+    }
+
+    #[test]
+    fn deep_paths_survive_the_7z_staging_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input");
+        // Well past Windows' 260-character limit once under the temp folder
+        let deep: String = (0..30).map(|i| format!("level-{:02}-folder/", i)).collect::<String>() + "leaf.txt";
+        write_files(&input, &[(deep.as_str(), b"deep")]);
+        let archive = dir.path().join("out.7z");
+        let options = EncryptOptions { kdf_cycles_power: Some(MIN_7Z_KDF_CYCLES_POWER), ..Default::default() };
+        let app = tauri::test::mock_app();
+        encrypt(&app, &input, &archive, EncryptionMethod::SevenZip, &options).unwrap();
+
+        let output = dir.path().join("extracted");
+        extract(&app, &archive, &output, PASSWORD, &DecryptOptions::default()).unwrap();
+        assert_eq!(extracted(&output, &input, &deep), b"deep");
+    }
+
+    #[test]
+    fn staging_errors_name_the_paths_and_the_fix() {
+        let (no_space, too_long) = STAGING_ERROR_CODES;
+        if let (Some(&no_space), Some(&too_long)) = (no_space.first(), too_long.first()) {
+            let error = staging_error("Failed to copy a to b".to_string(), std::io::Error::from_raw_os_error(too_long));
+            assert!(error.starts_with("Failed to copy a to b: "), "{}", error);
+            assert!(error.contains("path too long for the temp folder"), "{}", error);
+            let error = staging_error("Failed to copy a to b".to_string(), std::io::Error::from_raw_os_error(no_space));
+            assert!(error.contains("out of space or has too many files"), "{}", error);
+        }
+        let error = staging_error("Failed to create c".to_string(), std::io::Error::other("boom"));
+        assert_eq!(error, "Failed to create c: boom");
+    }
+
+    #[test]
+    fn unicode_path_field_round_trips_accented_and_cjk_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input");
+        let names = ["café crème.txt", "日本語のファイル.txt", "plain.txt"];
+        let files: Vec<(&str, &[u8])> = names.iter().map(|n| (*n, n.as_bytes())).collect();
+        write_files(&input, &files);
+        let archive = dir.path().join("out.zip");
+        let app = tauri::test::mock_app();
+        encrypt(&app, &input, &archive, EncryptionMethod::Aes256, &EncryptOptions::default()).unwrap();
+
+        let mut zip = zip::ZipArchive::new(File::open(&archive).unwrap()).unwrap();
+        for name in names {
+            let entry_name = format!("input/{}", name);
+            let index = zip.index_for_name(&entry_name).unwrap();
+            let file = zip.by_index_raw(index).unwrap();
+            let field = zip_extra_field(&file, 0x7075);
+            if name.is_ascii() {
+                assert!(field.is_none(), "{}", name);
+                continue;
+            }
+            let field = field.unwrap();
+            assert_eq!(field[0], 1);
+            assert_eq!(&field[5..], entry_name.as_bytes());
+        }
+
+        let output = dir.path().join("extracted");
+        extract(&app, &archive, &output, PASSWORD, &DecryptOptions::default()).unwrap();
+        for name in names {
+            assert_eq!(extracted(&output, &input, name), name.as_bytes());
+        }
+    }
+
+    #[test]
+    fn wrong_password_fails_before_the_size_pass() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input");
+        let files: Vec<(String, Vec<u8>)> = (0..20).map(|i| (format!("f{}.txt", i), vec![b'x'; 100])).collect();
+        let refs: Vec<(&str, &[u8])> = files.iter().map(|(n, d)| (n.as_str(), d.as_slice())).collect();
+        write_files(&input, &refs);
+        let archive = dir.path().join("out.zip");
+        let app = tauri::test::mock_app();
+        encrypt(&app, &input, &archive, EncryptionMethod::Aes256, &EncryptOptions::default()).unwrap();
+
+        let jobs = JobRegistry::default();
+        let job = jobs.start(JobKind::Decrypt, None).unwrap();
+        let _current = job.enter();
+        let output = dir.path().join("extracted");
+        let error = extract(&app, &archive, &output, "not the password", &DecryptOptions::default()).err().unwrap();
+        assert_eq!(error, "Mot de passe incorrect");
+        // Still on the opening step, the size calculation never started
+        assert_eq!(jobs.progress(job.id).unwrap().status, "Ouverture de l'archive...");
+        assert_eq!(fs::read_dir(&output).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn compression_specs_parse() {
+        assert!(matches!(parse_compression_spec("store"), Ok(CompressionSpec::Zip(BenchMethod::Stored, None))));
+        assert!(matches!(parse_compression_spec("deflate:9"), Ok(CompressionSpec::Zip(BenchMethod::Deflated, Some(9)))));
+        assert!(matches!(parse_compression_spec(" ZSTD : 19 "), Ok(CompressionSpec::Zip(BenchMethod::Zstd, Some(19)))));
+        assert!(matches!(parse_compression_spec("zstd:-7"), Ok(CompressionSpec::Zip(BenchMethod::Zstd, Some(-7)))));
+        assert!(matches!(parse_compression_spec("bzip2"), Ok(CompressionSpec::Zip(BenchMethod::Bzip2, None))));
+        assert!(matches!(parse_compression_spec("7z:5"), Ok(CompressionSpec::SevenZip(Some(5)))));
+        assert!(matches!(parse_compression_spec("lzma2"), Ok(CompressionSpec::SevenZip(None))));
+    }
+
+    #[test]
+    fn invalid_compression_specs_are_explained() {
+        let error = |spec| parse_compression_spec(spec).err().unwrap();
+        assert_eq!(error("zip:5"), "Unknown compression method: zip (expected store, deflate, bzip2, zstd or 7z)");
+        assert_eq!(error("deflate:fast"), "Invalid compression level: fast");
+        assert_eq!(error("deflate:10"), "Level must be between 0 and 9 for this method");
+        assert_eq!(error("zstd:23"), "Level must be between -7 and 22 for this method");
+        assert_eq!(error("store:1"), "Store takes no compression level");
+        assert_eq!(error("7z:10"), "Level must be between 0 and 9 for 7z");
+    }
+
+    #[test]
+    fn apply_profile_keeps_profile_values_the_call_leaves_out() {
+        let settings = EncryptionSettings {
+            encryption_method: EncryptionMethod::SevenZip,
+            options: EncryptOptions { deterministic: true, retry_count: 3, ..Default::default() },
+        };
+        let overrides = serde_json::json!({ "profile": "nightly", "retryCount": 5 });
+
+        let (method, options) = apply_profile(settings, None, overrides).unwrap();
+        assert!(matches!(method, EncryptionMethod::SevenZip));
+        assert!(options.deterministic);
+        assert_eq!(options.retry_count, 5);
+    }
 }
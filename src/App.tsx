@@ -96,8 +96,13 @@ function App() {
     setErrorMessage(null);
     setSuccessMessage(null);
     setZipOutputPath(null);
-    await invoke("cancel_encryption");
-    toast.info("Chiffrement annulé.", { duration: 2000 });
+    if (mode === 'encrypt') {
+      await invoke("cancel_encryption");
+      toast.info("Chiffrement annulé.", { duration: 2000 });
+    } else {
+      await invoke("cancel_decryption");
+      toast.info("Déchiffrement annulé.", { duration: 2000 });
+    }
   };
 
   const generatePassword = async () => {
@@ -230,14 +235,14 @@ function App() {
       });
 
       if (savePath) {
-        const result = await invoke("encrypt_files", {
+        const result = await invoke<{ message: string }>("encrypt_files", {
           filePaths: droppedFiles.map(f => f.path),
           outputPath: savePath,
           password,
           encryptionMethod,
         });
 
-        if (result === "Encryption cancelled by user.") {
+        if (result.message === "Encryption cancelled by user.") {
           toast.info("Chiffrement de l'archive annulé !", { duration: 2000 });
         } else {
           setSuccessMessage(`Archive exportée vers ${savePath}`);